@@ -0,0 +1,41 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// The access level AWS assigns to every action.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum AccessLevel {
+    List,
+    Read,
+    Write,
+    #[serde(rename = "Permissions management")]
+    PermissionsManagement,
+    Tagging,
+}
+
+impl fmt::Display for AccessLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessLevel::List => f.write_str("List"),
+            AccessLevel::Read => f.write_str("Read"),
+            AccessLevel::Write => f.write_str("Write"),
+            AccessLevel::PermissionsManagement => f.write_str("Permissions management"),
+            AccessLevel::Tagging => f.write_str("Tagging"),
+        }
+    }
+}
+
+impl FromStr for AccessLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "List" => Ok(AccessLevel::List),
+            "Read" => Ok(AccessLevel::Read),
+            "Write" => Ok(AccessLevel::Write),
+            "Permissions management" => Ok(AccessLevel::PermissionsManagement),
+            "Tagging" => Ok(AccessLevel::Tagging),
+            _ => Err(format!("Unknown access level: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,454 @@
+use std::{
+    collections::{BTreeSet, VecDeque},
+    fmt,
+};
+
+use crate::policy::{action_covers, resource_covers, PolicyDocument, PolicyStatement};
+
+use super::{Catalog, ResourceType};
+
+/// An action and a resource of a statement that do not fit together, since
+/// none of the resource types of the action matches the resource.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ResourceMismatch {
+    /// The file or ARN the policy was read from.
+    pub source: String,
+    /// The index of the statement within the policy.
+    pub statement: usize,
+    pub action: String,
+    pub resource: String,
+}
+
+impl fmt::Display for ResourceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: statement {}: action {} does not apply to resource {}",
+            self.source, self.statement, self.action, self.resource
+        )
+    }
+}
+
+/// Checks every pair of Action and Resource of the document against the
+/// resource types of the catalog.
+///
+/// A wildcard action fits a resource if one of the actions it matches does.
+/// `*` resources, NotAction and NotResource lists, actions unknown to the
+/// catalog and wildcards of services the catalog does not have all actions of
+/// are not checked.
+pub fn check_resource_types(
+    catalog: &Catalog,
+    source: &str,
+    document: &PolicyDocument,
+) -> Vec<ResourceMismatch> {
+    document
+        .statement
+        .iter()
+        .enumerate()
+        .flat_map(|(index, statement)| {
+            check_statement(catalog, statement)
+                .into_iter()
+                .map(move |(action, resource)| ResourceMismatch {
+                    source: source.to_string(),
+                    statement: index,
+                    action,
+                    resource,
+                })
+        })
+        .collect()
+}
+
+fn check_statement(catalog: &Catalog, statement: &PolicyStatement) -> Vec<(String, String)> {
+    let resources: Vec<&String> = statement
+        .resource
+        .iter()
+        .filter(|resource| resource.as_str() != "*")
+        .collect();
+
+    let mut mismatches = Vec::new();
+    for action in statement.action.iter().filter(|action| *action != "*") {
+        let formats = match resource_formats(catalog, action) {
+            Some(formats) => formats,
+            None => continue,
+        };
+        for resource in resources.iter() {
+            if !formats
+                .iter()
+                .any(|resource_type| arn_matches(resource_type, resource))
+            {
+                mismatches.push((action.clone(), resource.to_string()));
+            }
+        }
+    }
+    mismatches
+}
+
+/// The resource types of the actions matched by the action, or `None` if it
+/// matches no action of the catalog or may match actions missing from it.
+fn resource_formats<'a>(catalog: &'a Catalog, action: &str) -> Option<Vec<&'a ResourceType>> {
+    let actions = match action.contains(['*', '?']) {
+        true if !covers_wildcard(catalog, action) => return None,
+        true => catalog.matching_actions(action),
+        false => vec![action.to_string()],
+    };
+
+    let mut formats: Vec<&ResourceType> = Vec::new();
+    let mut known = false;
+    for action in actions {
+        let Some((prefix, name)) = action.split_once(':') else {
+            continue;
+        };
+        let Some(service) = catalog.service(prefix) else {
+            continue;
+        };
+        let Some((_, action)) = service.action(name) else {
+            continue;
+        };
+        known = true;
+        formats.extend(
+            action
+                .resource_types
+                .iter()
+                .filter_map(|resource_type| service.resource_types.get(resource_type)),
+        );
+    }
+    known.then_some(formats)
+}
+
+/// Returns true if the catalog has every action a wildcard action may match.
+fn covers_wildcard(catalog: &Catalog, action: &str) -> bool {
+    match action.split_once(':') {
+        Some((prefix, _)) if !prefix.contains(['*', '?']) => {
+            catalog.complete_service(prefix).is_some()
+        }
+        _ => catalog.is_complete(),
+    }
+}
+
+/// Returns true if the documents already grant the action of the mismatch on
+/// its resource: every action of the catalog it matches is granted on the
+/// resource by an Allow statement of theirs without conditions, listing both
+/// in its Action and Resource. A merge of the documents did not create such a
+/// mismatch.
+pub fn granted_by(
+    catalog: &Catalog,
+    mismatch: &ResourceMismatch,
+    documents: &[PolicyDocument],
+) -> bool {
+    let actions = match mismatch.action.contains(['*', '?']) {
+        true => catalog.matching_actions(&mismatch.action),
+        false => vec![mismatch.action.clone()],
+    };
+    actions.iter().all(|action| {
+        documents
+            .iter()
+            .flat_map(|document| document.statement.iter())
+            .filter(|statement| statement.effect == "Allow" && statement.condition.is_empty())
+            .any(|statement| {
+                statement.action.iter().any(|a| action_covers(a, action))
+                    && statement
+                        .resource
+                        .iter()
+                        .any(|r| resource_covers(r, &mismatch.resource))
+            })
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Char(char),
+    /// A `${...}` placeholder of an ARN format, matching one or more
+    /// characters except colons and slashes, unless it is a path.
+    Placeholder {
+        path: bool,
+    },
+    /// A `*` of a resource.
+    Any,
+    /// A `?` of a resource.
+    One,
+}
+
+/// Returns true if an ARN exists that is matched by both the ARN format of a
+/// resource type and the resource, which may contain wildcards.
+///
+/// Only the last placeholder of a resource type that allows paths may contain
+/// slashes and colons. This keeps an object ARN from matching the format of a
+/// bucket.
+fn arn_matches(resource_type: &ResourceType, resource: &str) -> bool {
+    let format = format_tokens(&resource_type.arn, resource_type.allows_paths);
+    let resource: Vec<Token> = resource
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            '*' => Token::Any,
+            '?' => Token::One,
+            c => Token::Char(c),
+        })
+        .collect();
+
+    // Every character of the ARNs matched by both, plus one that appears in
+    // neither, to stand for the rest.
+    let mut alphabet: BTreeSet<char> = format
+        .iter()
+        .chain(resource.iter())
+        .filter_map(|token| match token {
+            Token::Char(c) => Some(*c),
+            _ => None,
+        })
+        .collect();
+    alphabet.extend([':', '/', '\u{1}']);
+
+    intersects(&format, &resource, &alphabet)
+}
+
+fn format_tokens(format: &str, allows_paths: bool) -> Vec<Token> {
+    let format = format.to_lowercase();
+    let mut tokens = Vec::new();
+    let mut rest = format.as_str();
+    while !rest.is_empty() {
+        match rest
+            .strip_prefix("${")
+            .and_then(|placeholder| placeholder.split_once('}'))
+        {
+            Some((_, after)) => {
+                tokens.push(Token::Placeholder {
+                    path: allows_paths && !after.contains("${"),
+                });
+                rest = after;
+            }
+            None => {
+                let c = rest.chars().next().unwrap_or_default();
+                tokens.push(Token::Char(c));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    tokens
+}
+
+/// Searches the product of both patterns for a path to the end of both. A
+/// state is the position in the format, whether the placeholder at that
+/// position has matched a character yet, and the position in the resource.
+fn intersects(format: &[Token], resource: &[Token], alphabet: &BTreeSet<char>) -> bool {
+    // Leaving a placeholder that matched a character and skipping a `*`
+    // consume nothing.
+    let closure = |state: (usize, bool, usize)| {
+        let mut states = vec![state];
+        let mut index = 0;
+        while let Some(&(f, started, r)) = states.get(index) {
+            if started {
+                states.push((f + 1, false, r));
+            }
+            if resource.get(r) == Some(&Token::Any) {
+                states.push((f, started, r + 1));
+            }
+            index += 1;
+        }
+        states
+    };
+
+    let mut visited = BTreeSet::new();
+    let mut queue: VecDeque<(usize, bool, usize)> = closure((0, false, 0)).into();
+    while let Some(state) = queue.pop_front() {
+        if !visited.insert(state) {
+            continue;
+        }
+        let (f, started, r) = state;
+        if f == format.len() && r == resource.len() {
+            return true;
+        }
+        for &c in alphabet.iter() {
+            let format_next = match format.get(f) {
+                Some(Token::Char(x)) if *x == c && !started => Some((f + 1, false)),
+                Some(Token::Placeholder { path }) if *path || (c != ':' && c != '/') => {
+                    Some((f, true))
+                }
+                _ => None,
+            };
+            let resource_next = match resource.get(r) {
+                Some(Token::Any) => Some(r),
+                Some(Token::One) => Some(r + 1),
+                Some(Token::Char(x)) if *x == c => Some(r + 1),
+                _ => None,
+            };
+            if let (Some((f, started)), Some(r)) = (format_next, resource_next) {
+                queue.extend(closure((f, started, r)));
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{Condition, ConditionStatement, PolicyStatement};
+    use crate::test_util::{allow, document, statement};
+
+    #[test]
+    fn test_arn_matches_resource_types() {
+        let catalog = Catalog::bundled().unwrap();
+        let resource_type = |prefix: &str, name: &str| {
+            catalog.service(prefix).unwrap().resource_types[name].clone()
+        };
+        let bucket = resource_type("s3", "bucket");
+        let object = resource_type("s3", "object");
+
+        assert!(arn_matches(&bucket, "arn:aws:s3:::bucket"));
+        assert!(!arn_matches(&bucket, "arn:aws:s3:::bucket/key"));
+        assert!(arn_matches(&object, "arn:aws:s3:::bucket/path/to/key"));
+        assert!(arn_matches(&object, "arn:aws:s3:::bucket/*"));
+        assert!(arn_matches(&object, "arn:aws:s3:::*"));
+        assert!(!arn_matches(&object, "arn:aws:s3:::bucket"));
+        assert!(arn_matches(
+            &resource_type("iam", "role"),
+            "arn:aws:iam::123456789012:role/service/Admin"
+        ));
+        assert!(arn_matches(
+            &resource_type("secretsmanager", "Secret"),
+            "arn:aws:secretsmanager:eu-west-1:123456789012:secret:prod/db/password-AbCdEf"
+        ));
+        assert!(!arn_matches(
+            &resource_type("sqs", "queue"),
+            "arn:aws:sns:eu-west-1:123456789012:topic"
+        ));
+        assert!(!arn_matches(
+            &resource_type("kms", "key"),
+            "arn:aws:kms:eu-west-1:123456789012:key/team/key-id"
+        ));
+    }
+
+    #[test]
+    fn test_check_reports_mismatched_pairs() {
+        let mismatches = check_resource_types(
+            &Catalog::bundled().unwrap(),
+            "policy.json",
+            &document(vec![allow(
+                &["s3:ListBucket", "s3:GetObject"],
+                &["arn:aws:s3:::bucket", "arn:aws:s3:::bucket/*"],
+            )]),
+        );
+
+        assert_eq!(
+            mismatches
+                .iter()
+                .map(|m| m.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "policy.json: statement 0: action s3:ListBucket does not apply to resource arn:aws:s3:::bucket/*",
+                "policy.json: statement 0: action s3:GetObject does not apply to resource arn:aws:s3:::bucket",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_skips_star_and_unknown_actions() {
+        let mismatches = check_resource_types(
+            &Catalog::bundled().unwrap(),
+            "policy.json",
+            &document(vec![allow(
+                &["s3:ListAllMyBuckets", "ec2:DescribeInstances", "*"],
+                &["*", "arn:aws:ec2:*:*:instance/*"],
+            )]),
+        );
+
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].action, "s3:ListAllMyBuckets");
+    }
+
+    #[test]
+    fn test_check_wildcard_action_fits_if_one_action_fits() {
+        let mismatches = check_resource_types(
+            &Catalog::bundled().unwrap(),
+            "policy.json",
+            &document(vec![allow(
+                &["s3:Get*"],
+                &["arn:aws:s3:::bucket/*", "arn:aws:s3:::bucket"],
+            )]),
+        );
+
+        assert!(mismatches.is_empty());
+    }
+
+    #[test]
+    fn test_check_skips_wildcards_of_partial_services() {
+        let mismatches = check_resource_types(
+            &Catalog::bundled().unwrap(),
+            "policy.json",
+            &document(vec![allow(
+                &["s3:ListB*", "sqs:Send*"],
+                &["arn:aws:s3:::bucket/*"],
+            )]),
+        );
+
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].action, "sqs:Send*");
+    }
+
+    #[test]
+    fn test_granted_by_compares_matched_actions() {
+        let catalog = Catalog::bundled().unwrap();
+        let mismatch = |action: &str| ResourceMismatch {
+            source: "merged.json".to_string(),
+            statement: 0,
+            action: action.to_string(),
+            resource: "arn:aws:s3:::bucket/*".to_string(),
+        };
+        let documents = [document(vec![allow(
+            &["s3:ListBucket", "s3:ListBucketVersions"],
+            &["arn:aws:s3:::bucket/*"],
+        )])];
+
+        assert!(granted_by(&catalog, &mismatch("S3:LISTBUCKET"), &documents));
+        assert!(granted_by(
+            &catalog,
+            &mismatch("s3:ListBucketV*"),
+            &documents
+        ));
+        assert!(!granted_by(
+            &catalog,
+            &mismatch("s3:ListBucket*"),
+            &documents
+        ));
+    }
+
+    #[test]
+    fn test_granted_by_counts_only_unconditional_allow_statements() {
+        let catalog = Catalog::bundled().unwrap();
+        let mismatch = ResourceMismatch {
+            source: "merged.json".to_string(),
+            statement: 0,
+            action: "s3:ListBucket".to_string(),
+            resource: "arn:aws:s3:::bucket/*".to_string(),
+        };
+        let granted_by_statement = |statement: PolicyStatement| {
+            granted_by(&catalog, &mismatch, &[document(vec![statement])])
+        };
+        let conditioned = PolicyStatement::new(
+            "Allow".to_string(),
+            vec!["s3:ListBucket".to_string()],
+            vec!["arn:aws:s3:::bucket/*".to_string()],
+            vec![ConditionStatement::new(
+                "Bool".parse().unwrap(),
+                vec![Condition::new(
+                    "aws:SecureTransport".to_string(),
+                    vec![true],
+                )],
+            )],
+        );
+        let mut not_action = allow(&[], &["arn:aws:s3:::bucket/*"]);
+        not_action.not_action = vec!["s3:GetObject".to_string()];
+
+        assert!(granted_by_statement(allow(
+            &["s3:ListBucket"],
+            &["arn:aws:s3:::bucket/*"]
+        )));
+        assert!(!granted_by_statement(statement(
+            "Deny",
+            &["s3:ListBucket"],
+            &["arn:aws:s3:::bucket/*"]
+        )));
+        assert!(!granted_by_statement(conditioned));
+        assert!(!granted_by_statement(not_action));
+    }
+}
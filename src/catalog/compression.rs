@@ -0,0 +1,166 @@
+use std::collections::BTreeSet;
+
+use crate::policy::{action_covers, PolicyDocument, PolicyStatement};
+
+use super::{Catalog, Service};
+
+/// Replaces actions listed one by one with prefix wildcards such as `sqs:Get*`.
+///
+/// A wildcard is only used for services the catalog has all actions of, and
+/// only if every action of the catalog it matches is already granted by the
+/// list, so the permissions stay the same at the version of the catalog. Every
+/// action gets the shortest such wildcard, which is kept only if it is shorter
+/// than the actions it replaces. Actions of other services and existing
+/// wildcards are kept as they are.
+pub fn compress_actions(catalog: &Catalog, actions: &[String]) -> Vec<String> {
+    let granted: BTreeSet<String> = catalog
+        .actions()
+        .map(|(name, _)| name)
+        .filter(|name| actions.iter().any(|action| action_covers(action, name)))
+        .map(|name| name.to_lowercase())
+        .collect();
+
+    let wildcards: Vec<Option<String>> = actions
+        .iter()
+        .map(|action| shortest_wildcard(catalog, &granted, action))
+        .collect();
+
+    let mut compressed: Vec<String> = Vec::new();
+    for (action, wildcard) in actions.iter().zip(wildcards.iter()) {
+        let replacement = match wildcard {
+            Some(wildcard) if worth_replacing(actions, &wildcards, wildcard) => wildcard,
+            _ => action,
+        };
+        if !compressed
+            .iter()
+            .any(|a| a.eq_ignore_ascii_case(replacement))
+        {
+            compressed.push(replacement.clone());
+        }
+    }
+    compressed
+}
+
+/// The shortest prefix wildcard of a known action matching only granted
+/// actions. Since a longer prefix matches fewer actions, the first valid
+/// prefix is the shortest.
+fn shortest_wildcard(
+    catalog: &Catalog,
+    granted: &BTreeSet<String>,
+    action: &str,
+) -> Option<String> {
+    if action.contains(['*', '?']) {
+        return None;
+    }
+    let (prefix, name) = action.split_once(':')?;
+    let service = catalog.complete_service(prefix)?;
+    let (name, _) = service.action(name)?;
+    let prefix = prefix.to_lowercase();
+
+    (0..=name.len())
+        .filter(|&length| name.is_char_boundary(length))
+        .map(|length| &name[..length])
+        .find(|start| {
+            prefixed_actions(service, start)
+                .all(|other| granted.contains(&format!("{}:{}", prefix, other.to_lowercase())))
+        })
+        .map(|start| format!("{}:{}*", prefix, start))
+}
+
+fn prefixed_actions<'a>(service: &'a Service, start: &'a str) -> impl Iterator<Item = &'a String> {
+    let start = start.to_lowercase();
+    service
+        .actions
+        .keys()
+        .filter(move |name| name.to_lowercase().starts_with(&start))
+}
+
+/// A wildcard replaces the actions it was chosen for if it is shorter than
+/// them in the serialized list.
+fn worth_replacing(actions: &[String], wildcards: &[Option<String>], wildcard: &str) -> bool {
+    let replaced: BTreeSet<String> = actions
+        .iter()
+        .zip(wildcards.iter())
+        .filter(|(_, w)| w.as_deref() == Some(wildcard))
+        .map(|(action, _)| action.to_lowercase())
+        .collect();
+    let replaced_size: usize = replaced.iter().map(|action| action.len() + 3).sum();
+    wildcard.len() + 3 < replaced_size
+}
+
+/// Compresses the Action and NotAction lists of every statement, see
+/// [`compress_actions`].
+pub fn compress_policy_document(catalog: &Catalog, document: &PolicyDocument) -> PolicyDocument {
+    PolicyDocument {
+        statement: document
+            .statement
+            .iter()
+            .map(|statement| compress_statement(catalog, statement))
+            .collect(),
+        ..document.clone()
+    }
+}
+
+fn compress_statement(catalog: &Catalog, statement: &PolicyStatement) -> PolicyStatement {
+    PolicyStatement {
+        action: compress_actions(catalog, &statement.action),
+        not_action: compress_actions(catalog, &statement.not_action),
+        ..statement.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::strings;
+
+    #[test]
+    fn test_compress_all_matching_actions() {
+        let catalog = Catalog::bundled().unwrap();
+        let assume = catalog.matching_actions("sts:Assume*");
+
+        let compressed = compress_actions(&catalog, &assume);
+
+        assert_eq!(compressed, strings(&["sts:A*"]));
+        assert_eq!(
+            catalog.matching_actions("sts:A*"),
+            catalog.matching_actions("sts:Assume*")
+        );
+    }
+
+    #[test]
+    fn test_compress_never_grants_more() {
+        let catalog = Catalog::bundled().unwrap();
+        let original = strings(&["sts:AssumeRole", "sts:AssumeRoleWithSAML", "s3:GetObject"]);
+
+        let compressed = compress_actions(&catalog, &original);
+
+        let granted = |list: &[String]| {
+            catalog
+                .actions()
+                .map(|(name, _)| name)
+                .filter(|name| list.iter().any(|a| action_covers(a, name)))
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(granted(&compressed), granted(&original));
+    }
+
+    #[test]
+    fn test_compress_keeps_actions_of_partial_services() {
+        let catalog = Catalog::bundled().unwrap();
+        let original = catalog.matching_actions("s3:GetObjectT*");
+
+        let compressed = compress_actions(&catalog, &original);
+
+        assert_eq!(compressed, original);
+    }
+
+    #[test]
+    fn test_compress_keeps_unknown_actions_and_wildcards() {
+        let original = strings(&["ec2:DescribeInstances", "s3:Get*", "sts:AssumeRole"]);
+
+        let compressed = compress_actions(&Catalog::bundled().unwrap(), &original);
+
+        assert_eq!(compressed, original);
+    }
+}
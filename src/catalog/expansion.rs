@@ -0,0 +1,137 @@
+use crate::policy::{PolicyDocument, PolicyStatement};
+
+use super::Catalog;
+
+/// Replaces every wildcard action with the actions of the catalog it matches.
+///
+/// Only wildcards with the prefix of a service in the catalog are expanded.
+/// `*`, wildcards in the service prefix and wildcards matching no action are
+/// kept, since the catalog may not contain every service or action and
+/// expanding them would narrow the permissions. For the same reason, a
+/// wildcard of a service the catalog does not have all actions of is kept in
+/// front of its expansion.
+pub fn expand_actions(catalog: &Catalog, actions: &[String]) -> Vec<String> {
+    let mut expanded: Vec<String> = Vec::new();
+    for action in actions {
+        for action in expand_action(catalog, action) {
+            if !expanded.iter().any(|a| a.eq_ignore_ascii_case(&action)) {
+                expanded.push(action);
+            }
+        }
+    }
+    expanded
+}
+
+fn expand_action(catalog: &Catalog, action: &str) -> Vec<String> {
+    if !expandable(catalog, action) {
+        return vec![action.to_string()];
+    }
+    let matching_actions = catalog.matching_actions(action);
+    match keeps_wildcard(catalog, action) {
+        true => std::iter::once(action.to_string())
+            .chain(matching_actions)
+            .collect(),
+        false => matching_actions,
+    }
+}
+
+/// Returns true if the action is a wildcard that [`expand_actions`] keeps in
+/// front of its expansion, because its service is not complete in the catalog.
+pub fn keeps_wildcard(catalog: &Catalog, action: &str) -> bool {
+    expandable(catalog, action)
+        && action
+            .split_once(':')
+            .and_then(|(prefix, _)| catalog.complete_service(prefix))
+            .is_none()
+}
+
+fn expandable(catalog: &Catalog, action: &str) -> bool {
+    let expandable = match action.split_once(':') {
+        Some((prefix, name)) => {
+            name.contains(['*', '?'])
+                && !prefix.contains(['*', '?'])
+                && catalog.service(prefix).is_some()
+        }
+        None => false,
+    };
+    expandable && !catalog.matching_actions(action).is_empty()
+}
+
+/// Expands the Action and NotAction lists of every statement, the inverse of
+/// [`PolicyStatement::reduce`].
+pub fn expand_policy_document(catalog: &Catalog, document: &PolicyDocument) -> PolicyDocument {
+    PolicyDocument {
+        statement: document
+            .statement
+            .iter()
+            .map(|statement| expand_statement(catalog, statement))
+            .collect(),
+        ..document.clone()
+    }
+}
+
+fn expand_statement(catalog: &Catalog, statement: &PolicyStatement) -> PolicyStatement {
+    PolicyStatement {
+        action: expand_actions(catalog, &statement.action),
+        not_action: expand_actions(catalog, &statement.not_action),
+        ..statement.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::strings;
+
+    #[test]
+    fn test_expand_wildcard() {
+        let expanded = expand_actions(&Catalog::bundled().unwrap(), &strings(&["sts:Assume*"]));
+
+        assert_eq!(
+            expanded,
+            strings(&[
+                "sts:AssumeRole",
+                "sts:AssumeRoleWithSAML",
+                "sts:AssumeRoleWithWebIdentity",
+                "sts:AssumeRoot",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_expand_keeps_actions_without_wildcard_once() {
+        let expanded = expand_actions(
+            &Catalog::bundled().unwrap(),
+            &strings(&["sqs:SendMessage", "sqs:Send*", "SQS:sendmessage"]),
+        );
+
+        assert_eq!(expanded, strings(&["sqs:SendMessage"]));
+    }
+
+    #[test]
+    fn test_expand_keeps_wildcard_of_partial_service() {
+        let expanded = expand_actions(&Catalog::bundled().unwrap(), &strings(&["s3:GetObjectT*"]));
+
+        assert_eq!(
+            expanded,
+            strings(&[
+                "s3:GetObjectT*",
+                "s3:GetObjectTagging",
+                "s3:GetObjectTorrent",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_expand_keeps_wildcards_outside_catalog() {
+        let expanded = expand_actions(
+            &Catalog::bundled().unwrap(),
+            &strings(&["*", "ec2:Describe*", "s*:GetObject", "s3:Fetch*"]),
+        );
+
+        assert_eq!(
+            expanded,
+            strings(&["*", "ec2:Describe*", "s*:GetObject", "s3:Fetch*"])
+        );
+    }
+}
@@ -0,0 +1,30 @@
+mod access_level;
+mod compatibility;
+mod compression;
+mod expansion;
+mod service;
+mod service_catalog;
+mod validation;
+
+pub use access_level::AccessLevel;
+
+pub use compatibility::check_resource_types;
+pub use compatibility::granted_by;
+pub use compatibility::ResourceMismatch;
+
+pub use compression::compress_actions;
+pub use compression::compress_policy_document;
+
+pub use expansion::expand_actions;
+pub use expansion::expand_policy_document;
+pub use expansion::keeps_wildcard;
+
+pub use service::Action;
+pub use service::ResourceType;
+pub use service::Service;
+
+pub use service_catalog::Catalog;
+
+pub use validation::validate_actions;
+pub use validation::ActionIssue;
+pub use validation::ActionIssueKind;
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::AccessLevel;
+
+/// A service of the catalog, identified by its action prefix such as `s3`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Service {
+    pub name: String,
+
+    /// Set if the service has all of its actions. Wildcards are only expanded
+    /// or introduced for complete services, since they would match actions
+    /// missing from the catalog.
+    #[serde(default)]
+    pub complete: bool,
+
+    /// The actions of the service keyed by their name without prefix.
+    pub actions: BTreeMap<String, Action>,
+
+    #[serde(default)]
+    pub resource_types: BTreeMap<String, ResourceType>,
+
+    /// The service specific condition keys and their types.
+    #[serde(default)]
+    pub condition_keys: BTreeMap<String, String>,
+}
+
+impl Service {
+    /// Looks up an action by its name without prefix, ignoring case.
+    pub fn action(&self, name: &str) -> Option<(&str, &Action)> {
+        self.actions
+            .iter()
+            .find(|(action_name, _)| action_name.eq_ignore_ascii_case(name))
+            .map(|(action_name, action)| (action_name.as_str(), action))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Action {
+    pub access_level: AccessLevel,
+
+    /// The resource types the action can be restricted to. An action without
+    /// resource types only supports `"Resource": "*"`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resource_types: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub condition_keys: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ResourceType {
+    /// The ARN format with placeholders such as `${Partition}` or `${BucketName}`.
+    pub arn: String,
+
+    /// Set if the last placeholder of the ARN may contain slashes and colons,
+    /// like the key of an S3 object or the path of an IAM role.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allows_paths: bool,
+}
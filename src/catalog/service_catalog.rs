@@ -0,0 +1,184 @@
+use std::{collections::BTreeMap, fs::File, io::BufReader};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::MaipError;
+use crate::policy::action_covers;
+
+use super::{Action, Service};
+
+const BUNDLED_CATALOG: &str = include_str!("service_authorization.json");
+
+/// An offline snapshot of the AWS Service Authorization Reference.
+///
+/// The bundled snapshot only covers a selection of services, and only some of
+/// them with all of their actions. A complete or more recent snapshot in the
+/// same format can be loaded with [`Catalog::from_file`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Catalog {
+    /// The date the snapshot was taken, e.g. `2024-11-15`.
+    pub version: String,
+
+    /// Set if the snapshot has every service, so that a prefix missing from it
+    /// is not a service.
+    #[serde(default)]
+    pub complete: bool,
+
+    /// The services keyed by their lowercase action prefix.
+    pub services: BTreeMap<String, Service>,
+}
+
+impl Catalog {
+    /// The snapshot bundled with maip.
+    pub fn bundled() -> Result<Self, MaipError> {
+        serde_json::from_str(BUNDLED_CATALOG).map_err(|e| MaipError::parse("bundled catalog", e))
+    }
+
+    pub fn from_file(file: &str) -> Result<Self, MaipError> {
+        let reader = BufReader::new(File::open(file).map_err(|e| MaipError::io(file, e))?);
+        let catalog: Catalog =
+            serde_json::from_reader(reader).map_err(|e| MaipError::parse(file, e))?;
+        Ok(catalog)
+    }
+
+    /// Loads the catalog from the given file, or the bundled one if there is none.
+    pub fn load(file: Option<&str>) -> Result<Self, MaipError> {
+        match file {
+            Some(file) => Self::from_file(file),
+            None => Self::bundled(),
+        }
+    }
+
+    /// Looks up a service by its action prefix, ignoring case.
+    pub fn service(&self, prefix: &str) -> Option<&Service> {
+        self.services.get(&prefix.to_lowercase())
+    }
+
+    /// Looks up a service like [`Catalog::service`], but only if the catalog
+    /// has all of its actions.
+    pub fn complete_service(&self, prefix: &str) -> Option<&Service> {
+        self.service(prefix).filter(|service| service.complete)
+    }
+
+    /// Returns true if the catalog has every service with all of its actions.
+    pub fn is_complete(&self) -> bool {
+        self.complete && self.services.values().all(|service| service.complete)
+    }
+
+    /// Looks up an action such as `s3:GetObject`, ignoring case.
+    pub fn action(&self, action: &str) -> Option<&Action> {
+        let (prefix, name) = action.split_once(':')?;
+        self.service(prefix)?.action(name).map(|(_, action)| action)
+    }
+
+    /// The actions of the catalog matched by an action that may contain wildcards.
+    pub fn matching_actions(&self, pattern: &str) -> Vec<String> {
+        self.actions()
+            .map(|(name, _)| name)
+            .filter(|name| action_covers(pattern, name))
+            .collect()
+    }
+
+    /// All actions of the catalog with their prefix, e.g. `s3:GetObject`.
+    pub fn actions(&self) -> impl Iterator<Item = (String, &Action)> {
+        self.services.iter().flat_map(|(prefix, service)| {
+            service
+                .actions
+                .iter()
+                .map(move |(name, action)| (format!("{}:{}", prefix, name), action))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::AccessLevel;
+
+    #[test]
+    fn test_bundled_catalog() {
+        let catalog = Catalog::bundled().unwrap();
+
+        assert!(!catalog.version.is_empty());
+        assert!(catalog.service("s3").is_some());
+        assert!(catalog.actions().count() > 100);
+    }
+
+    #[test]
+    fn test_bundled_catalog_is_partial() {
+        let catalog = Catalog::bundled().unwrap();
+
+        assert!(!catalog.is_complete());
+        assert!(catalog.complete_service("sts").is_some());
+        assert!(catalog.service("s3").is_some());
+        assert!(catalog.complete_service("s3").is_none());
+        assert!(catalog.service("ec2").is_none());
+    }
+
+    #[test]
+    fn test_bundled_catalog_resource_types_exist() {
+        let catalog = Catalog::bundled().unwrap();
+
+        for (prefix, service) in catalog.services.iter() {
+            for (name, action) in service.actions.iter() {
+                for resource_type in action.resource_types.iter() {
+                    assert!(
+                        service.resource_types.contains_key(resource_type),
+                        "{}:{} references unknown resource type {}",
+                        prefix,
+                        name,
+                        resource_type
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_action_lookup_ignores_case() {
+        let catalog = Catalog::bundled().unwrap();
+
+        let action = catalog.action("S3:getobject").unwrap();
+
+        assert_eq!(action.access_level, AccessLevel::Read);
+        assert_eq!(action.resource_types, vec!["object"]);
+        assert!(catalog.action("s3:GetObjects").is_none());
+        assert!(catalog.action("unknown:GetObject").is_none());
+        assert!(catalog.action("s3").is_none());
+    }
+
+    #[test]
+    fn test_matching_actions() {
+        let catalog = Catalog::bundled().unwrap();
+
+        assert_eq!(
+            catalog.matching_actions("sts:Assume*WithSAML"),
+            vec!["sts:AssumeRoleWithSAML"]
+        );
+        assert_eq!(
+            catalog.matching_actions("SQS:?urgeQueue"),
+            vec!["sqs:PurgeQueue"]
+        );
+        assert!(catalog.matching_actions("s3:Fetch*").is_empty());
+    }
+
+    #[test]
+    fn test_catalog_from_json() {
+        let catalog: Catalog = serde_json::from_str(
+            r#"{"Version":"2024-01-01","Complete":true,"Services":{"example":{"Name":"Example","Complete":true,"Actions":{"GetThing":{"AccessLevel":"Read"},"TagThing":{"AccessLevel":"Tagging"}}}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            catalog.action("example:TagThing").unwrap().access_level,
+            AccessLevel::Tagging
+        );
+        assert!(catalog
+            .service("example")
+            .unwrap()
+            .resource_types
+            .is_empty());
+        assert!(catalog.is_complete());
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+
+use crate::policy::PolicyDocument;
+
+use super::Catalog;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ActionIssueKind {
+    /// No service of the catalog has the prefix of the action, although the
+    /// catalog has every service.
+    UnknownService,
+    /// The service exists with all of its actions, but has no action with this
+    /// name.
+    UnknownAction,
+    /// A wildcard action that matches no action of the catalog, although the
+    /// catalog has every action it could match.
+    NoMatch,
+    /// An action the catalog does not know, but that may still exist, since
+    /// the catalog does not have its service or not all of its actions.
+    NotVerified,
+}
+
+/// An action of a policy that does not grant anything according to the catalog,
+/// or that could not be checked.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ActionIssue {
+    /// The file or ARN the policy was read from.
+    pub source: String,
+    /// The index of the statement within the policy.
+    pub statement: usize,
+    pub action: String,
+    pub kind: ActionIssueKind,
+}
+
+impl ActionIssue {
+    /// Returns true if the action is known to be invalid, false if it could
+    /// only not be verified.
+    pub fn is_invalid(&self) -> bool {
+        self.kind != ActionIssueKind::NotVerified
+    }
+}
+
+impl fmt::Display for ActionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self.kind {
+            ActionIssueKind::UnknownService => "unknown service prefix in action",
+            ActionIssueKind::UnknownAction => "unknown action",
+            ActionIssueKind::NoMatch => "wildcard matches no action",
+            ActionIssueKind::NotVerified => {
+                return write!(
+                    f,
+                    "{}: statement {}: {} not verified: service not complete in catalog",
+                    self.source, self.statement, self.action
+                )
+            }
+        };
+        write!(
+            f,
+            "{}: statement {}: {} {}",
+            self.source, self.statement, problem, self.action
+        )
+    }
+}
+
+/// Checks every Action and NotAction of the document against the catalog.
+///
+/// Actions the catalog does not know are reported as not verified instead of
+/// invalid if the catalog does not fully cover them: those of services missing
+/// from a partial catalog and those of services without all actions.
+pub fn validate_actions(
+    catalog: &Catalog,
+    source: &str,
+    document: &PolicyDocument,
+) -> Vec<ActionIssue> {
+    document
+        .statement
+        .iter()
+        .enumerate()
+        .flat_map(|(index, statement)| {
+            statement
+                .action
+                .iter()
+                .chain(statement.not_action.iter())
+                .filter_map(move |action| {
+                    Some(ActionIssue {
+                        source: source.to_string(),
+                        statement: index,
+                        action: action.clone(),
+                        kind: check_action(catalog, action)?,
+                    })
+                })
+        })
+        .collect()
+}
+
+fn check_action(catalog: &Catalog, action: &str) -> Option<ActionIssueKind> {
+    if action == "*" {
+        return None;
+    }
+
+    let prefix = action.split_once(':').map_or(action, |(prefix, _)| prefix);
+    let has_wildcard = |value: &str| value.contains(['*', '?']);
+
+    let complete = if has_wildcard(prefix) {
+        catalog.is_complete()
+    } else {
+        match catalog.service(prefix) {
+            None if catalog.complete => return Some(ActionIssueKind::UnknownService),
+            None => return Some(ActionIssueKind::NotVerified),
+            Some(service) => service.complete,
+        }
+    };
+
+    let unknown = match has_wildcard(action) {
+        true => catalog.matching_actions(action).is_empty(),
+        false => catalog.action(action).is_none(),
+    };
+    match (unknown, complete) {
+        (false, _) => None,
+        (true, false) => Some(ActionIssueKind::NotVerified),
+        (true, true) if has_wildcard(action) => Some(ActionIssueKind::NoMatch),
+        (true, true) => Some(ActionIssueKind::UnknownAction),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{allow, document};
+
+    fn allow_all(actions: &[&str]) -> PolicyDocument {
+        document(vec![allow(actions, &["*"])])
+    }
+
+    #[test]
+    fn test_validate_known_actions() {
+        let issues = validate_actions(
+            &Catalog::bundled().unwrap(),
+            "policy.json",
+            &allow_all(&[
+                "*",
+                "s3:GetObject",
+                "S3:getobject",
+                "s3:Get*",
+                "s3:*",
+                "*:GetObject",
+            ]),
+        );
+
+        assert!(issues.is_empty());
+    }
+
+    /// The complete services of the bundled catalog, as a catalog that has
+    /// every service.
+    fn complete_catalog() -> Catalog {
+        let mut catalog = Catalog::bundled().unwrap();
+        catalog.services.retain(|_, service| service.complete);
+        catalog.complete = true;
+        catalog
+    }
+
+    #[test]
+    fn test_validate_unknown_actions() {
+        let issues = validate_actions(
+            &complete_catalog(),
+            "policy.json",
+            &allow_all(&["sqs:SendMessages", "s4:GetObject", "sqs:Fetch*", "sqs"]),
+        );
+
+        assert_eq!(
+            issues.iter().map(|i| i.kind).collect::<Vec<_>>(),
+            vec![
+                ActionIssueKind::UnknownAction,
+                ActionIssueKind::UnknownService,
+                ActionIssueKind::NoMatch,
+                ActionIssueKind::UnknownAction,
+            ]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "policy.json: statement 0: unknown action sqs:SendMessages"
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_actions_not_verified() {
+        let issues = validate_actions(
+            &Catalog::bundled().unwrap(),
+            "policy.json",
+            &allow_all(&[
+                "s3:GetObjects",
+                "s4:GetObject",
+                "iam:PassRoles",
+                "s3:Fetch*",
+                "*:Fetch*",
+                "sqs:SendMessages",
+            ]),
+        );
+
+        assert_eq!(
+            issues.iter().map(|i| i.kind).collect::<Vec<_>>(),
+            vec![
+                ActionIssueKind::NotVerified,
+                ActionIssueKind::NotVerified,
+                ActionIssueKind::NotVerified,
+                ActionIssueKind::NotVerified,
+                ActionIssueKind::NotVerified,
+                ActionIssueKind::UnknownAction,
+            ]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "policy.json: statement 0: s3:GetObjects not verified: service not complete in catalog"
+        );
+        assert_eq!(issues.iter().filter(|i| i.is_invalid()).count(), 1);
+    }
+
+    #[test]
+    fn test_validate_not_action() {
+        let mut document = allow_all(&[]);
+        document.statement[0].not_action = vec!["sts:AssumeRoles".to_string()];
+
+        let issues = validate_actions(&Catalog::bundled().unwrap(), "policy.json", &document);
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, ActionIssueKind::UnknownAction);
+    }
+}
@@ -33,14 +33,14 @@ impl CLI {
     }
 
     pub fn parse(&mut self, args: Vec<String>) -> Result<(), String> {
-        let option_args = parse_option_args(&args)?;
-        if let Some(command) = args.get(0) {
+        if let Some(command) = args.first() {
             let command = self
                 .commands
                 .iter_mut()
                 .find(|c| c.name() == command)
                 .ok_or(format!("Command not found: {}", command))?;
 
+            let option_args = parse_option_args(&args, &command.flag_args())?;
             command.set_option_args(&option_args)?;
             command.run()?;
 
@@ -52,13 +52,22 @@ impl CLI {
     }
 }
 
-fn parse_option_args(args: &[String]) -> Result<HashMap<String, Vec<String>>, String> {
+fn parse_option_args(
+    args: &[String],
+    flags: &[String],
+) -> Result<HashMap<String, Vec<String>>, String> {
     let mut option_args: HashMap<String, Vec<String>> = HashMap::new();
     for (i, arg) in args.iter().enumerate() {
         if !arg.starts_with("--") {
             continue;
         }
 
+        let flag = remove_prefix(arg);
+        if flags.iter().any(|f| f == flag) {
+            push_to_value_if_present(&mut option_args, flag.to_string(), "true".to_string());
+            continue;
+        }
+
         let (key, value) = get_equals_pair(arg)
             .or(get_index_pair(i, args))
             .ok_or(format!("Missing value for key: {}", args[i]))?;
@@ -109,7 +118,7 @@ mod tests {
             String::from("value"),
         ];
 
-        let result = super::parse_option_args(&args).unwrap();
+        let result = super::parse_option_args(&args, &[]).unwrap();
 
         assert_eq!(result.get("key").unwrap(), &vec!["value", "value"]);
     }
@@ -118,9 +127,23 @@ mod tests {
     fn test_parse_option_args_missing_value() {
         let args = vec![String::from("--key")];
 
-        let result = super::parse_option_args(&args);
+        let result = super::parse_option_args(&args, &[]);
 
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Missing value for key: --key");
     }
+
+    #[test]
+    fn test_parse_option_args_flag() {
+        let args = vec![
+            String::from("--flag"),
+            String::from("--key"),
+            String::from("value"),
+        ];
+
+        let result = super::parse_option_args(&args, &[String::from("flag")]).unwrap();
+
+        assert_eq!(result.get("flag").unwrap(), &vec!["true"]);
+        assert_eq!(result.get("key").unwrap(), &vec!["value"]);
+    }
 }
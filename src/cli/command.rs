@@ -8,6 +8,11 @@ pub trait Command: Arguments {
     fn required_args(&self) -> Vec<String>;
 
     fn optional_args(&self) -> Vec<String>;
+
+    /// Options that take no value, set to `true` when present.
+    fn flag_args(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 pub trait Arguments {
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use crate::catalog::Catalog;
+use crate::cli::{Arguments, Command};
+
+/// Prints the services of the bundled catalog, or of a catalog file given with
+/// `--catalog`.
+#[derive(Default)]
+pub struct ShowCatalog {
+    catalog: Option<String>,
+}
+
+impl Arguments for ShowCatalog {
+    fn set_option_args(&mut self, args: &HashMap<String, Vec<String>>) -> Result<(), String> {
+        if let Some(catalog) = args.get("catalog") {
+            self.catalog = Some(
+                catalog
+                    .first()
+                    .ok_or("Missing value for --catalog option".to_string())?
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    fn set_positional_args(&mut self, _: &[String]) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl Command for ShowCatalog {
+    fn run(&self) -> Result<(), String> {
+        let catalog = Catalog::load(self.catalog.as_deref()).map_err(|e| e.to_string())?;
+
+        println!("Catalog version {}", catalog.version);
+        for (prefix, service) in catalog.services.iter() {
+            println!(
+                "{}: {} ({} actions, {})",
+                prefix,
+                service.name,
+                service.actions.len(),
+                match service.complete {
+                    true => "complete",
+                    false => "partial",
+                }
+            );
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "catalog"
+    }
+
+    fn required_args(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn optional_args(&self) -> Vec<String> {
+        vec!["catalog".to_string()]
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use crate::catalog::{expand_policy_document, keeps_wildcard, Catalog};
+use crate::cli::{Arguments, Command};
+use crate::policy::{policy_to_file, PolicyDocument};
+
+use super::input::read_documents;
+
+/// Rewrites the wildcard actions of a policy into the actions they match.
+#[derive(Default)]
+pub struct Expand {
+    files: Vec<String>,
+    arns: Vec<String>,
+    out: String,
+    catalog: Option<String>,
+    annotate: bool,
+}
+
+impl Arguments for Expand {
+    fn set_option_args(&mut self, args: &HashMap<String, Vec<String>>) -> Result<(), String> {
+        if let Some(files) = args.get("file") {
+            self.files = files.clone();
+        }
+        if let Some(arns) = args.get("arn") {
+            self.arns = arns.clone();
+        }
+        if let Some(out) = args.get("out") {
+            self.out = out
+                .first()
+                .ok_or("Missing value for --out option".to_string())?
+                .to_string();
+        }
+        if let Some(catalog) = args.get("catalog") {
+            self.catalog = Some(
+                catalog
+                    .first()
+                    .ok_or("Missing value for --catalog option".to_string())?
+                    .to_string(),
+            );
+        }
+        if let Some(format) = args.get("format") {
+            self.annotate = match format
+                .first()
+                .ok_or("Missing value for --format option".to_string())?
+                .as_str()
+            {
+                "json" => false,
+                "annotated" => true,
+                format => {
+                    return Err(format!(
+                        "Unknown format: {}, expected json or annotated",
+                        format
+                    ))
+                }
+            };
+        }
+        Ok(())
+    }
+
+    fn set_positional_args(&mut self, _: &[String]) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl Command for Expand {
+    fn run(&self) -> Result<(), String> {
+        let (_, documents) = read_documents(&self.files, &self.arns, "")?;
+        let document = match documents.as_slice() {
+            [document] => document,
+            _ => return Err("Expand takes exactly one policy".to_string()),
+        };
+
+        let catalog = Catalog::load(self.catalog.as_deref()).map_err(|e| e.to_string())?;
+        let expanded = expand_policy_document(&catalog, document);
+
+        if self.annotate {
+            print!("{}", annotate(&catalog, &expanded));
+            return Ok(());
+        }
+        if self.out.is_empty() {
+            println!("{}", serde_json::to_string_pretty(&expanded).unwrap());
+            return Ok(());
+        }
+        policy_to_file(self.out.as_str(), &expanded).map_err(|e| e.to_string())
+    }
+
+    fn name(&self) -> &str {
+        "expand"
+    }
+
+    fn required_args(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn optional_args(&self) -> Vec<String> {
+        vec![
+            "file".to_string(),
+            "arn".to_string(),
+            "out".to_string(),
+            "catalog".to_string(),
+            "format".to_string(),
+        ]
+    }
+}
+
+/// Lists the actions of every statement of an expanded document with their
+/// access level, or `-` if it is unknown. Wildcards of partial services kept in
+/// front of their expansion are left out.
+fn annotate(catalog: &Catalog, document: &PolicyDocument) -> String {
+    let mut output = String::new();
+    for (index, statement) in document.statement.iter().enumerate() {
+        let name = match statement.sid.as_ref() {
+            Some(sid) => sid.clone(),
+            None => index.to_string(),
+        };
+        let (label, actions) = if statement.action.is_empty() {
+            ("NotAction", &statement.not_action)
+        } else {
+            ("Action", &statement.action)
+        };
+        let actions: Vec<&String> = actions
+            .iter()
+            .filter(|action| !keeps_wildcard(catalog, action))
+            .collect();
+        output.push_str(&format!(
+            "Statement {} ({}, {}):\n",
+            name, statement.effect, label
+        ));
+
+        let width = actions.iter().map(|a| a.len()).max().unwrap_or(0);
+        for action in actions {
+            let access_level = catalog
+                .action(action)
+                .map_or("-".to_string(), |a| a.access_level.to_string());
+            output.push_str(&format!(
+                "  {:width$}  {}\n",
+                action,
+                access_level,
+                width = width
+            ));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::catalog::Catalog;
+    use crate::policy::{PolicyDocument, PolicyStatement};
+
+    #[test]
+    fn test_annotate() {
+        let document = PolicyDocument::new(
+            "2012-10-17".to_string(),
+            vec![PolicyStatement::new(
+                "Allow".to_string(),
+                vec![
+                    "*".to_string(),
+                    "s*:GetObject".to_string(),
+                    "sqs:SendMessage".to_string(),
+                    "sqs:TagQueue".to_string(),
+                    "s3:GetObjectT*".to_string(),
+                    "s3:GetObjectTagging".to_string(),
+                ],
+                vec!["*".to_string()],
+                Vec::new(),
+            )
+            .with_sid("Queue")],
+        );
+
+        let output = super::annotate(&Catalog::bundled().unwrap(), &document);
+
+        assert_eq!(
+            output,
+            "Statement Queue (Allow, Action):\n  *                    -\n  s*:GetObject         -\n  sqs:SendMessage      Write\n  sqs:TagQueue         Tagging\n  s3:GetObjectTagging  Read\n"
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use std::fs::{read_dir, ReadDir};
+
+use crate::policy::{
+    policy_from_arn, policy_from_file, PolicyDocument, VERSION_2008_10_17, VERSION_2012_10_17,
+};
+
+/// Reads the documents of the given files, ARNs and all JSON files of the
+/// directory `all`. Returns where each document was read from and the documents,
+/// upgraded to the current version.
+pub fn read_documents(
+    files: &[String],
+    arns: &[String],
+    all: &str,
+) -> Result<(Vec<String>, Vec<PolicyDocument>), String> {
+    let mut documents = files_to_documents(files)?;
+    documents.extend(arns_to_documents(arns)?);
+    let mut sources = files.to_vec();
+    sources.extend(arns.iter().cloned());
+
+    if !all.is_empty() {
+        let directory = read_dir(all).map_err(|e| format!("Failed to read directory: {}", e))?;
+        let files = get_json_files(directory);
+        documents.extend(files_to_documents(&files)?);
+        sources.extend(files);
+    }
+
+    Ok((sources, documents))
+}
+
+fn files_to_documents(files: &[String]) -> Result<Vec<PolicyDocument>, String> {
+    files
+        .iter()
+        .map(|file| upgrade_document(file, policy_from_file(file).map_err(|e| e.to_string())?))
+        .collect()
+}
+
+fn arns_to_documents(arns: &[String]) -> Result<Vec<PolicyDocument>, String> {
+    arns.iter()
+        .map(|arn| upgrade_document(arn, policy_from_arn(arn).map_err(|e| e.to_string())?))
+        .collect()
+}
+
+fn upgrade_document(source: &str, mut document: PolicyDocument) -> Result<PolicyDocument, String> {
+    if document.effective_version() == VERSION_2008_10_17 {
+        eprintln!(
+            "Warning: {}: upgrading from version {} to {}",
+            source, VERSION_2008_10_17, VERSION_2012_10_17
+        );
+    }
+    for warning in document.upgrade().map_err(|e| e.to_string())? {
+        eprintln!("Warning: {}: {}", source, warning);
+    }
+    Ok(document)
+}
+
+fn get_json_files(directory: ReadDir) -> Vec<String> {
+    directory
+        .filter_map(|f| {
+            let file = f.ok()?;
+            let path = file.path();
+            let extension = path.extension()?;
+            if !path.is_file() || extension != "json" {
+                return None;
+            }
+            Some(path.to_str()?.to_string())
+        })
+        .collect::<Vec<String>>()
+}
@@ -1,24 +1,39 @@
 use std::collections::HashMap;
-use std::fs::{read_dir, ReadDir};
+use std::path::Path;
 
+use crate::catalog::{compress_policy_document, Catalog};
 use crate::cli::{Arguments, Command};
 use crate::policy::{
-    merge_policy_documents, policy_from_arn, policy_from_file, policy_to_file, PolicyDocument,
+    merge_policy_documents_with_options, pack_policy_documents_with, policy_to_file,
+    split_policy_document, verify_equivalence, ConflictPolicy, MergeOptions, MergeStrategy,
+    PolicyDocument, PolicyTarget, StatementConflict,
 };
 
+use super::input::read_documents;
+use super::validate::{validate_after_merge, validate_before_merge, ValidationMode};
+
 #[derive(Default)]
 pub struct Merge {
     files: Vec<String>,
     arns: Vec<String>,
     out: String,
     all: String,
+    conflict_policy: ConflictPolicy,
+    strategy: MergeStrategy,
+    max_size: Option<usize>,
+    target: Option<PolicyTarget>,
+    max_policies: Option<usize>,
+    validation: Option<ValidationMode>,
+    catalog: Option<String>,
+    compress: bool,
+    verify: bool,
 }
 
 impl Arguments for Merge {
     fn set_option_args(&mut self, args: &HashMap<String, Vec<String>>) -> Result<(), String> {
         if let Some(out) = args.get("out") {
             self.out = out
-                .get(0)
+                .first()
                 .ok_or("Missing value for --out option".to_string())?
                 .to_string();
         }
@@ -28,9 +43,69 @@ impl Arguments for Merge {
         if let Some(arns) = args.get("arn") {
             self.arns = arns.clone();
         }
+        if let Some(conflict_policy) = args.get("on-conflict") {
+            self.conflict_policy = conflict_policy
+                .first()
+                .ok_or("Missing value for --on-conflict option".to_string())?
+                .parse()?;
+        }
+        if let Some(strategy) = args.get("strategy") {
+            self.strategy = strategy
+                .first()
+                .ok_or("Missing value for --strategy option".to_string())?
+                .parse()?;
+        }
+        if let Some(max_size) = args.get("max-size") {
+            let max_size = max_size
+                .first()
+                .ok_or("Missing value for --max-size option".to_string())?;
+            self.max_size = Some(
+                max_size
+                    .parse()
+                    .map_err(|_| format!("Invalid value for --max-size option: {}", max_size))?,
+            );
+        }
+        if let Some(max_policies) = args.get("max-policies") {
+            let max_policies = max_policies
+                .first()
+                .ok_or("Missing value for --max-policies option".to_string())?;
+            self.max_policies = Some(max_policies.parse().map_err(|_| {
+                format!("Invalid value for --max-policies option: {}", max_policies)
+            })?);
+        }
+        if let Some(validation) = args.get("validate") {
+            self.validation = Some(
+                validation
+                    .first()
+                    .ok_or("Missing value for --validate option".to_string())?
+                    .parse()?,
+            );
+        }
+        if let Some(catalog) = args.get("catalog") {
+            self.catalog = Some(
+                catalog
+                    .first()
+                    .ok_or("Missing value for --catalog option".to_string())?
+                    .to_string(),
+            );
+        }
+        if args.contains_key("compress") {
+            self.compress = true;
+        }
+        if args.contains_key("verify") {
+            self.verify = true;
+        }
+        if let Some(target) = args.get("target") {
+            self.target = Some(
+                target
+                    .first()
+                    .ok_or("Missing value for --target option".to_string())?
+                    .parse()?,
+            );
+        }
         if let Some(all) = args.get("all") {
             self.all = all
-                .get(0)
+                .first()
                 .ok_or("Missing value for --all option".to_string())?
                 .to_string();
         }
@@ -42,27 +117,143 @@ impl Arguments for Merge {
     }
 }
 
+impl Merge {
+    fn merge_documents(
+        &self,
+        documents: &[PolicyDocument],
+        options: &MergeOptions,
+        catalog: Option<&Catalog>,
+    ) -> Result<Vec<PolicyDocument>, String> {
+        let outcome =
+            merge_policy_documents_with_options(documents, options).map_err(|e| e.to_string())?;
+        print_conflicts(&outcome.conflicts);
+
+        let mut result = outcome.document;
+        if let Some(catalog) = catalog {
+            result = compress_policy_document(catalog, &result);
+        }
+        result.reduce();
+        result.sort();
+
+        match self.max_size {
+            Some(max_size) => split_policy_document(&result, max_size).map_err(|e| e.to_string()),
+            None => Ok(vec![result]),
+        }
+    }
+
+    /// The size limit used for packing, `--max-size` if given, otherwise the
+    /// limit of the target.
+    fn size_limit(&self) -> usize {
+        self.max_size
+            .unwrap_or_else(|| self.target.unwrap_or_default().size_limit())
+    }
+
+    fn output_name(&self, index: usize, count: usize) -> String {
+        match (self.out.is_empty(), count) {
+            (true, _) => format!("policy {}", index + 1),
+            (false, 1) => self.out.clone(),
+            (false, _) => numbered_file(&self.out, index + 1),
+        }
+    }
+}
+
 impl Command for Merge {
     fn run(&self) -> Result<(), String> {
-        let mut documents = files_to_documents(&self.files)?;
-        documents.extend(arns_to_documents(&self.arns)?);
+        let (sources, documents) = read_documents(&self.files, &self.arns, &self.all)?;
 
-        if !self.all.is_empty() {
-            let directory = read_dir(self.all.as_str())
-                .map_err(|e| format!("Failed to read directory: {}", e))?;
-            let files = get_json_files(directory);
-            documents.extend(files_to_documents(&files)?);
+        if documents.is_empty() {
+            return Err("No documents to merge".to_string());
         }
 
-        let mut result = merge_documents(&documents)?;
-        result.reduce();
-        result.sort();
+        if let Some(validation) = self.validation {
+            validate_before_merge(validation, self.catalog.as_deref(), &sources, &documents)?;
+        }
+
+        let catalog = match self.compress || self.verify {
+            true => Some(Catalog::load(self.catalog.as_deref()).map_err(|e| e.to_string())?),
+            false => None,
+        };
+        let compression = catalog.as_ref().filter(|_| self.compress);
+
+        let options = MergeOptions {
+            conflict_policy: self.conflict_policy,
+            strategy: self.strategy,
+        };
+        let (results, placements) = match self.max_policies {
+            Some(max_policies) => {
+                let outcome = pack_policy_documents_with(
+                    &documents,
+                    max_policies,
+                    self.size_limit(),
+                    &options,
+                    &|document| match compression {
+                        Some(catalog) => compress_policy_document(catalog, document),
+                        None => document.clone(),
+                    },
+                )
+                .map_err(|e| e.to_string())?;
+                print_conflicts(&outcome.conflicts);
+                (outcome.documents, Some(outcome.placements))
+            }
+            None => (
+                self.merge_documents(&documents, &options, compression)?,
+                None,
+            ),
+        };
+
+        if let Some(validation) = self.validation {
+            let names: Vec<String> = (0..results.len())
+                .map(|index| self.output_name(index, results.len()))
+                .collect();
+            validate_after_merge(
+                validation,
+                self.catalog.as_deref(),
+                &documents,
+                &names,
+                &results,
+            )?;
+        }
+
+        if let Some(catalog) = catalog.as_ref().filter(|_| self.verify) {
+            let actions: Vec<String> = catalog.actions().map(|(name, _)| name).collect();
+            let complete_services: Vec<String> = catalog
+                .services
+                .iter()
+                .filter(|(_, service)| service.complete)
+                .map(|(prefix, _)| prefix.clone())
+                .collect();
+            verify_equivalence(&documents, &results, &actions, &complete_services)
+                .map_err(|e| e.to_string())?;
+        }
+
+        if let Some(target) = self.target {
+            for result in results.iter() {
+                target.check_size(result).map_err(|e| e.to_string())?;
+            }
+        }
+
+        if let Some(placements) = placements {
+            for (source, placement) in sources.iter().zip(placements) {
+                let outputs = placement
+                    .into_iter()
+                    .map(|index| self.output_name(index, results.len()))
+                    .collect::<Vec<String>>();
+                eprintln!("{} -> {}", source, outputs.join(", "));
+            }
+        }
 
         if self.out.is_empty() {
-            println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            let output = match results.as_slice() {
+                [result] => serde_json::to_string_pretty(result),
+                results => serde_json::to_string_pretty(results),
+            };
+            println!("{}", output.unwrap());
             return Ok(());
         }
-        policy_to_file(self.out.as_str(), &result)?;
+        for (index, result) in results.iter().enumerate() {
+            let out = self.output_name(index, results.len());
+            policy_to_file(out.as_str(), result).map_err(|e| e.to_string())?;
+        }
         Ok(())
     }
 
@@ -75,42 +266,110 @@ impl Command for Merge {
     }
 
     fn optional_args(&self) -> Vec<String> {
-        vec!["file".to_string(), "out".to_string(), "all".to_string()]
+        vec![
+            "file".to_string(),
+            "out".to_string(),
+            "all".to_string(),
+            "on-conflict".to_string(),
+            "strategy".to_string(),
+            "max-size".to_string(),
+            "target".to_string(),
+            "max-policies".to_string(),
+            "validate".to_string(),
+            "catalog".to_string(),
+        ]
+    }
+
+    fn flag_args(&self) -> Vec<String> {
+        vec!["compress".to_string(), "verify".to_string()]
     }
 }
 
-fn files_to_documents(files: &[String]) -> Result<Vec<PolicyDocument>, String> {
-    files
-        .iter()
-        .map(|file| policy_from_file(file.as_str()))
-        .collect()
+/// Inserts the number before the extension, `merged.json` becomes `merged-1.json`.
+fn numbered_file(file: &str, number: usize) -> String {
+    let path = Path::new(file);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(file);
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(extension) => format!("{}-{}.{}", stem, number, extension),
+        None => format!("{}-{}", stem, number),
+    };
+    path.with_file_name(name).to_string_lossy().to_string()
 }
 
-fn arns_to_documents(arns: &[String]) -> Result<Vec<PolicyDocument>, String> {
-    arns.iter()
-        .map(|arn| policy_from_arn(arn.as_str()))
-        .collect()
+fn print_conflicts(conflicts: &[StatementConflict]) {
+    for conflict in conflicts {
+        eprintln!("Conflict: {}", conflict);
+    }
 }
 
-fn get_json_files(directory: ReadDir) -> Vec<String> {
-    directory
-        .filter_map(|f| {
-            let file = f.ok()?;
-            let path = file.path();
-            let extension = path.extension()?;
-            if !path.is_file() || extension != "json" {
-                return None;
-            }
-            Some(path.to_str()?.to_string())
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+
+    use super::*;
+    use crate::policy::{policy_from_file, Condition, ConditionStatement, PolicyStatement};
+
+    #[test]
+    fn test_compression_helps_packing() {
+        let catalog = Catalog::bundled().unwrap();
+        let directory = temp_dir();
+        let files: Vec<String> = [
+            "aws:PrincipalTag/team",
+            "aws:RequestedRegion",
+            "aws:SourceVpc",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(index, key)| {
+            let statement = PolicyStatement::new(
+                "Allow".to_string(),
+                catalog.matching_actions("kms:*"),
+                vec!["*".to_string()],
+                vec![ConditionStatement::new(
+                    "StringEquals".parse().unwrap(),
+                    vec![Condition::new(key.to_string(), vec!["value"])],
+                )],
+            );
+            let file = directory.join(format!("maip_compress_pack_{}.json", index));
+            let file = file.to_str().unwrap().to_string();
+            policy_to_file(
+                &file,
+                &PolicyDocument::new("2012-10-17".to_string(), vec![statement]),
+            )
+            .unwrap();
+            file
         })
-        .collect::<Vec<String>>()
-}
+        .collect();
+        let out = directory.join("maip_compress_pack_merged.json");
+        let out = out.to_str().unwrap().to_string();
+
+        let mut merge = Merge::default();
+        merge
+            .set_option_args(&HashMap::from([
+                ("file".to_string(), files),
+                ("out".to_string(), vec![out.clone()]),
+                ("target".to_string(), vec!["user-inline".to_string()]),
+                ("max-policies".to_string(), vec!["1".to_string()]),
+                ("compress".to_string(), vec!["true".to_string()]),
+            ]))
+            .unwrap();
+        merge.run().unwrap();
 
-fn merge_documents(documents: &[PolicyDocument]) -> Result<PolicyDocument, String> {
-    if documents.is_empty() {
-        return Err("No documents to merge".to_string());
+        let merged = policy_from_file(&out).unwrap();
+        assert_eq!(merged.statement.len(), 3);
+        assert!(merged
+            .statement
+            .iter()
+            .all(|statement| statement.action == vec!["kms:*"]));
     }
 
-    let result = merge_policy_documents(documents)?;
-    Ok(result)
+    #[test]
+    fn test_numbered_file() {
+        assert_eq!(super::numbered_file("merged.json", 1), "merged-1.json");
+        assert_eq!(
+            super::numbered_file("out/merged.json", 2),
+            "out/merged-2.json"
+        );
+        assert_eq!(super::numbered_file("merged", 3), "merged-3");
+    }
 }
@@ -1,3 +1,10 @@
+mod catalog;
+mod expand;
+mod input;
 mod merge;
+mod validate;
 
-pub use merge::Merge;
\ No newline at end of file
+pub use catalog::ShowCatalog;
+pub use expand::Expand;
+pub use merge::Merge;
+pub use validate::Validate;
\ No newline at end of file
@@ -0,0 +1,200 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::catalog::{
+    check_resource_types, granted_by, validate_actions, ActionIssue, Catalog, ResourceMismatch,
+};
+use crate::cli::{Arguments, Command};
+use crate::policy::PolicyDocument;
+
+use super::input::read_documents;
+
+/// Checks the actions of policies and the resources they are used with against
+/// the service catalog.
+#[derive(Default)]
+pub struct Validate {
+    files: Vec<String>,
+    arns: Vec<String>,
+    all: String,
+    catalog: Option<String>,
+}
+
+impl Arguments for Validate {
+    fn set_option_args(&mut self, args: &HashMap<String, Vec<String>>) -> Result<(), String> {
+        if let Some(files) = args.get("file") {
+            self.files = files.clone();
+        }
+        if let Some(arns) = args.get("arn") {
+            self.arns = arns.clone();
+        }
+        if let Some(all) = args.get("all") {
+            self.all = all
+                .first()
+                .ok_or("Missing value for --all option".to_string())?
+                .to_string();
+        }
+        if let Some(catalog) = args.get("catalog") {
+            self.catalog = Some(
+                catalog
+                    .first()
+                    .ok_or("Missing value for --catalog option".to_string())?
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    fn set_positional_args(&mut self, _: &[String]) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl Command for Validate {
+    fn run(&self) -> Result<(), String> {
+        let (sources, documents) = read_documents(&self.files, &self.arns, &self.all)?;
+        if documents.is_empty() {
+            return Err("No documents to validate".to_string());
+        }
+
+        let catalog = Catalog::load(self.catalog.as_deref()).map_err(|e| e.to_string())?;
+        let (issues, unverified) = find_issues(&catalog, &sources, &documents);
+        for issue in issues.iter() {
+            println!("{}", issue);
+        }
+        for issue in unverified.iter() {
+            eprintln!("Warning: {}", issue);
+        }
+        let mismatches = find_mismatches(&catalog, &sources, &documents);
+        for mismatch in mismatches.iter() {
+            println!("{}", mismatch);
+        }
+
+        if !issues.is_empty() {
+            return Err(format!("Found {} invalid actions", issues.len()));
+        }
+        if !mismatches.is_empty() {
+            return Err(format!(
+                "Found {} actions used with resources they do not apply to",
+                mismatches.len()
+            ));
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "validate"
+    }
+
+    fn required_args(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn optional_args(&self) -> Vec<String> {
+        vec![
+            "file".to_string(),
+            "arn".to_string(),
+            "all".to_string(),
+            "catalog".to_string(),
+        ]
+    }
+}
+
+/// What the merge command does with invalid actions in its input.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ValidationMode {
+    /// Print the invalid actions and merge anyway.
+    Warn,
+    /// Print the invalid actions and fail.
+    Fail,
+}
+
+impl FromStr for ValidationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "warn" => Ok(ValidationMode::Warn),
+            "fail" => Ok(ValidationMode::Fail),
+            _ => Err(format!(
+                "Unknown validation mode: {}, expected warn or fail",
+                s
+            )),
+        }
+    }
+}
+
+/// Validates the input of a merge, printing every invalid action to stderr.
+pub fn validate_before_merge(
+    mode: ValidationMode,
+    catalog: Option<&str>,
+    sources: &[String],
+    documents: &[PolicyDocument],
+) -> Result<(), String> {
+    let catalog = Catalog::load(catalog).map_err(|e| e.to_string())?;
+    let (issues, unverified) = find_issues(&catalog, sources, documents);
+    for issue in issues.iter().chain(unverified.iter()) {
+        eprintln!("Warning: {}", issue);
+    }
+
+    if mode == ValidationMode::Fail && !issues.is_empty() {
+        return Err(format!(
+            "Found {} invalid actions, not merging",
+            issues.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the merged documents for actions combined with resources they do not
+/// apply to. Pairs that the input already granted, compared on the actions of
+/// the catalog they match, are not reported, so only pairings created by the
+/// merge are found.
+pub fn validate_after_merge(
+    mode: ValidationMode,
+    catalog: Option<&str>,
+    documents: &[PolicyDocument],
+    names: &[String],
+    results: &[PolicyDocument],
+) -> Result<(), String> {
+    let catalog = Catalog::load(catalog).map_err(|e| e.to_string())?;
+    let mismatches: Vec<ResourceMismatch> = find_mismatches(&catalog, names, results)
+        .into_iter()
+        .filter(|mismatch| !granted_by(&catalog, mismatch, documents))
+        .collect();
+    for mismatch in mismatches.iter() {
+        eprintln!("Warning: {}", mismatch);
+    }
+
+    if mode == ValidationMode::Fail && !mismatches.is_empty() {
+        return Err(format!(
+            "The merge combined {} actions with resources they do not apply to",
+            mismatches.len()
+        ));
+    }
+    Ok(())
+}
+
+fn find_mismatches(
+    catalog: &Catalog,
+    sources: &[String],
+    documents: &[PolicyDocument],
+) -> Vec<ResourceMismatch> {
+    sources
+        .iter()
+        .zip(documents.iter())
+        .flat_map(|(source, document)| check_resource_types(catalog, source, document))
+        .collect()
+}
+
+/// The invalid actions of the documents and the actions that could not be
+/// verified.
+fn find_issues(
+    catalog: &Catalog,
+    sources: &[String],
+    documents: &[PolicyDocument],
+) -> (Vec<ActionIssue>, Vec<ActionIssue>) {
+    sources
+        .iter()
+        .zip(documents.iter())
+        .flat_map(|(source, document)| validate_actions(catalog, source, document))
+        .partition(ActionIssue::is_invalid)
+}
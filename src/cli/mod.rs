@@ -1,4 +1,5 @@
 mod command;
+#[allow(clippy::module_inception)]
 mod cli;
 pub mod commands;
 
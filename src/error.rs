@@ -0,0 +1,124 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum MaipError {
+    /// A policy document is not valid JSON or not a valid policy.
+    Parse {
+        path: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    UnsupportedVersion(String),
+    /// A `2008-10-17` document contains policy variables and has to be upgraded first.
+    UpgradeRequired(String),
+    AwsFetch {
+        arn: String,
+        message: String,
+    },
+    Io {
+        path: String,
+        source: io::Error,
+    },
+    /// The async runtime used to fetch policies could not be started.
+    Runtime(io::Error),
+    MergeConflict(String),
+    /// A policy or statement is larger than the size limit.
+    SizeLimit {
+        subject: String,
+        size: usize,
+        limit: usize,
+    },
+    /// The statements do not fit into the allowed number of policies.
+    PackingFailed {
+        required: usize,
+        max_policies: usize,
+        limit: usize,
+    },
+}
+
+impl MaipError {
+    pub fn parse(path: &str, error: serde_json::Error) -> Self {
+        MaipError::Parse {
+            path: path.to_string(),
+            line: error.line(),
+            column: error.column(),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn io(path: &str, source: io::Error) -> Self {
+        MaipError::Io {
+            path: path.to_string(),
+            source,
+        }
+    }
+
+    pub fn aws_fetch(arn: &str, message: impl fmt::Display) -> Self {
+        MaipError::AwsFetch {
+            arn: arn.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for MaipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaipError::Parse {
+                path,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "Failed to parse {} at line {}, column {}: {}",
+                path, line, column, message
+            ),
+            MaipError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported policy version: {}", version)
+            }
+            MaipError::UpgradeRequired(version) => write!(
+                f,
+                "Version {} documents with policy variables have to be upgraded before merging",
+                version
+            ),
+            MaipError::AwsFetch { arn, message } => {
+                write!(f, "Failed to fetch policy {}: {}", arn, message)
+            }
+            MaipError::Io { path, source } => write!(f, "Failed to access {}: {}", path, source),
+            MaipError::Runtime(source) => write!(f, "Failed to start the async runtime: {}", source),
+            MaipError::MergeConflict(message) => write!(f, "Merge conflict: {}", message),
+            MaipError::SizeLimit {
+                subject,
+                size,
+                limit,
+            } => write!(
+                f,
+                "{} has {} characters, {} more than the limit of {}",
+                subject,
+                size,
+                size.saturating_sub(*limit),
+                limit
+            ),
+            MaipError::PackingFailed {
+                required,
+                max_policies,
+                limit,
+            } => write!(
+                f,
+                "The policies need {} documents of at most {} characters, but at most {} are allowed",
+                required, limit, max_policies
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MaipError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MaipError::Io { source, .. } | MaipError::Runtime(source) => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,17 @@
 use std::{fmt, marker::PhantomData};
 
-use serde::{de, Deserialize, Deserializer};
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+/// A string or list of strings, for places where a field attribute cannot be
+/// used, such as values read from a map visitor.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct StringOrVec(
+    #[serde(
+        deserialize_with = "string_or_seq_string",
+        serialize_with = "serialize_string_or_vec"
+    )]
+    pub Vec<String>,
+);
 
 pub fn string_or_seq_string<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
 where
@@ -43,3 +54,39 @@ where
         serializer.collect_seq(value)
     }
 }
+
+pub fn object_or_seq<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    struct ObjectOrVec<T>(PhantomData<Vec<T>>);
+
+    impl<'de, T> de::Visitor<'de> for ObjectOrVec<T>
+    where
+        T: Deserialize<'de>,
+    {
+        type Value = Vec<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("object or list of objects")
+        }
+
+        fn visit_map<M>(self, visitor: M) -> Result<Self::Value, M::Error>
+        where
+            M: de::MapAccess<'de>,
+        {
+            let value = Deserialize::deserialize(de::value::MapAccessDeserializer::new(visitor))?;
+            Ok(vec![value])
+        }
+
+        fn visit_seq<S>(self, visitor: S) -> Result<Self::Value, S::Error>
+        where
+            S: de::SeqAccess<'de>,
+        {
+            Deserialize::deserialize(de::value::SeqAccessDeserializer::new(visitor))
+        }
+    }
+
+    deserializer.deserialize_any(ObjectOrVec(PhantomData))
+}
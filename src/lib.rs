@@ -1,3 +1,7 @@
+pub mod error;
 pub mod policy;
 pub mod json_string_or_vec;
-pub mod cli;
\ No newline at end of file
+pub mod cli;
+pub mod catalog;
+#[cfg(test)]
+mod test_util;
\ No newline at end of file
@@ -1,18 +1,22 @@
 use std::env;
 
-use maip::cli::commands::Merge;
+use maip::cli::commands::{Expand, Merge, ShowCatalog, Validate};
 use maip::cli::CLI;
 
 fn main() {
     let mut cli = CLI::build()
         .description("maip")
         .version("0.1.0")
-        .command(Merge::default());
+        .command(Merge::default())
+        .command(ShowCatalog::default())
+        .command(Validate::default())
+        .command(Expand::default());
 
     let args = get_args();
 
     if let Some(err) = cli.parse(args).err() {
-        println!("{}", err);
+        eprintln!("{}", err);
+        std::process::exit(1);
     }
 }
 
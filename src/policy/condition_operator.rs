@@ -0,0 +1,214 @@
+use std::{fmt, str::FromStr};
+
+/// A condition operator such as `StringEquals`, `ForAnyValue:StringLike` or
+/// `ArnLikeIfExists`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct ConditionOperator {
+    pub qualifier: Option<SetQualifier>,
+    pub kind: ConditionOperatorKind,
+    pub if_exists: bool,
+}
+
+impl ConditionOperator {
+    pub fn new(kind: ConditionOperatorKind) -> Self {
+        Self {
+            qualifier: None,
+            kind,
+            if_exists: false,
+        }
+    }
+
+    pub fn with_qualifier(mut self, qualifier: SetQualifier) -> Self {
+        self.qualifier = Some(qualifier);
+        self
+    }
+
+    pub fn if_exists(mut self) -> Self {
+        self.if_exists = true;
+        self
+    }
+
+    /// Returns true for operators that match when the value is not one of the
+    /// given values, like `StringNotEquals` or `NotIpAddress`.
+    pub fn is_negated(&self) -> bool {
+        use ConditionOperatorKind::*;
+
+        matches!(
+            self.kind,
+            StringNotEquals
+                | StringNotEqualsIgnoreCase
+                | StringNotLike
+                | NumericNotEquals
+                | DateNotEquals
+                | NotIpAddress
+                | ArnNotEquals
+                | ArnNotLike
+        )
+    }
+
+    /// Returns true if a condition with this operator matches whenever the
+    /// request value equals any of its values, so that two conditions on the
+    /// same key can be combined by a union of their values.
+    pub fn is_set_safe(&self) -> bool {
+        !self.is_negated() && self.qualifier != Some(SetQualifier::ForAllValues)
+    }
+}
+
+impl fmt::Display for ConditionOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(qualifier) = self.qualifier {
+            write!(f, "{}:", qualifier)?;
+        }
+        write!(f, "{}", self.kind)?;
+        if self.if_exists {
+            f.write_str(IF_EXISTS)?;
+        }
+        Ok(())
+    }
+}
+
+const IF_EXISTS: &str = "IfExists";
+
+impl FromStr for ConditionOperator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (qualifier, operator) = match s.split_once(':') {
+            Some((qualifier, operator)) => (Some(qualifier.parse::<SetQualifier>()?), operator),
+            None => (None, s),
+        };
+
+        let (operator, if_exists) = match operator.strip_suffix(IF_EXISTS) {
+            Some(operator) => (operator, true),
+            None => (operator, false),
+        };
+
+        let kind = operator
+            .parse::<ConditionOperatorKind>()
+            .map_err(|_| format!("Unknown condition operator: {}", s))?;
+
+        if kind == ConditionOperatorKind::Null && if_exists {
+            return Err(format!("Unknown condition operator: {}", s));
+        }
+
+        Ok(Self {
+            qualifier,
+            kind,
+            if_exists,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum SetQualifier {
+    ForAllValues,
+    ForAnyValue,
+}
+
+impl fmt::Display for SetQualifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetQualifier::ForAllValues => f.write_str("ForAllValues"),
+            SetQualifier::ForAnyValue => f.write_str("ForAnyValue"),
+        }
+    }
+}
+
+impl FromStr for SetQualifier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ForAllValues" => Ok(SetQualifier::ForAllValues),
+            "ForAnyValue" => Ok(SetQualifier::ForAnyValue),
+            _ => Err(format!("Unknown set qualifier: {}", s)),
+        }
+    }
+}
+
+macro_rules! condition_operator_kinds {
+    ($($kind:ident),* $(,)?) => {
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+        pub enum ConditionOperatorKind {
+            $($kind),*
+        }
+
+        impl fmt::Display for ConditionOperatorKind {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $(ConditionOperatorKind::$kind => f.write_str(stringify!($kind))),*
+                }
+            }
+        }
+
+        impl FromStr for ConditionOperatorKind {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $(stringify!($kind) => Ok(ConditionOperatorKind::$kind),)*
+                    _ => Err(format!("Unknown condition operator: {}", s)),
+                }
+            }
+        }
+    };
+}
+
+condition_operator_kinds!(
+    StringEquals,
+    StringNotEquals,
+    StringEqualsIgnoreCase,
+    StringNotEqualsIgnoreCase,
+    StringLike,
+    StringNotLike,
+    NumericEquals,
+    NumericNotEquals,
+    NumericLessThan,
+    NumericLessThanEquals,
+    NumericGreaterThan,
+    NumericGreaterThanEquals,
+    DateEquals,
+    DateNotEquals,
+    DateLessThan,
+    DateLessThanEquals,
+    DateGreaterThan,
+    DateGreaterThanEquals,
+    Bool,
+    BinaryEquals,
+    IpAddress,
+    NotIpAddress,
+    ArnEquals,
+    ArnLike,
+    ArnNotEquals,
+    ArnNotLike,
+    Null,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_qualified_if_exists_operator() {
+        let operator = "ForAnyValue:StringLikeIfExists"
+            .parse::<ConditionOperator>()
+            .unwrap();
+
+        assert_eq!(
+            operator,
+            ConditionOperator::new(ConditionOperatorKind::StringLike)
+                .with_qualifier(SetQualifier::ForAnyValue)
+                .if_exists()
+        );
+        assert_eq!(operator.to_string(), "ForAnyValue:StringLikeIfExists");
+    }
+
+    #[test]
+    fn test_parse_unknown_operator() {
+        assert!("StringEqual".parse::<ConditionOperator>().is_err());
+        assert!("ForSomeValues:StringEquals"
+            .parse::<ConditionOperator>()
+            .is_err());
+        assert!("NullIfExists".parse::<ConditionOperator>().is_err());
+    }
+}
@@ -1,29 +1,200 @@
-#[derive(Debug, Default, PartialEq, Clone)]
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use serde_json::Number;
+
+use super::{merge::Merge, ConditionOperator};
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct ConditionStatement {
-    pub operator: String,
+    pub operator: ConditionOperator,
     pub conditions: Vec<Condition>,
 }
 
 impl ConditionStatement {
-    pub fn new(operator: String, conditions: Vec<Condition>) -> Self {
+    pub fn new(operator: ConditionOperator, conditions: Vec<Condition>) -> Self {
         Self {
             operator,
             conditions,
         }
     }
+
+    pub fn sort(&mut self) {
+        self.conditions.sort_by(|a, b| a.key.cmp(&b.key));
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Condition {
     pub key: String,
-    pub values: Vec<String>,
+    pub values: Vec<ConditionValue>,
 }
 
 impl Condition {
-    pub fn new(condition_key: String, condition_value: Vec<String>) -> Self {
+    pub fn new<V: Into<ConditionValue>>(condition_key: String, condition_value: Vec<V>) -> Self {
         Self {
             key: condition_key,
-            values: condition_value,
+            values: condition_value.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A single condition value, kept in the JSON type it was written with so that
+/// `false` and `10` are not turned into `"false"` and `"10"`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ConditionValue {
+    String(String),
+    Bool(bool),
+    Number(Number),
+    Null,
+}
+
+impl ConditionValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ConditionValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Condition blocks keyed by operator and condition key, with the values as a set.
+type CanonicalConditions = BTreeMap<(ConditionOperator, String), BTreeSet<(u8, String)>>;
+
+/// Builds the order independent form of a list of condition statements. Returns
+/// `None` if a condition key appears twice below the same operator, since both
+/// conditions have to hold and they cannot be combined into one value list.
+fn canonical_conditions(
+    condition_statements: &[ConditionStatement],
+) -> Option<CanonicalConditions> {
+    let mut canonical = CanonicalConditions::new();
+    for condition_statement in condition_statements {
+        for condition in &condition_statement.conditions {
+            let values = condition
+                .values
+                .iter()
+                .map(ConditionValue::sort_key)
+                .collect();
+            let key = (condition_statement.operator, condition.key.clone());
+            if canonical.insert(key, values).is_some() {
+                return None;
+            }
+        }
+    }
+    Some(canonical)
+}
+
+/// Orders lists of condition statements by their canonical form, so that equal
+/// conditions compare as equal regardless of the order of operators and keys.
+pub fn compare_conditions(first: &[ConditionStatement], second: &[ConditionStatement]) -> Ordering {
+    canonical_conditions(first).cmp(&canonical_conditions(second))
+}
+
+/// Returns true if both lists describe the same conditions, regardless of the
+/// order of operators, keys and values.
+pub fn same_conditions(first: &[ConditionStatement], second: &[ConditionStatement]) -> bool {
+    if first == second {
+        return true;
+    }
+    match (canonical_conditions(first), canonical_conditions(second)) {
+        (Some(first), Some(second)) => first == second,
+        _ => false,
+    }
+}
+
+/// Combines two condition lists that differ only in the values of a single key
+/// into one list matching whenever either of them matches.
+///
+/// This is only possible for operators that match if the request value equals
+/// any of the listed values. For negated operators and `ForAllValues` a value
+/// union would change the meaning of the condition.
+pub fn union_condition_values(
+    first: &[ConditionStatement],
+    second: &[ConditionStatement],
+) -> Option<Vec<ConditionStatement>> {
+    let first_canonical = canonical_conditions(first)?;
+    let second_canonical = canonical_conditions(second)?;
+
+    if !first_canonical.keys().eq(second_canonical.keys()) {
+        return None;
+    }
+
+    let mut differences = first_canonical
+        .iter()
+        .zip(second_canonical.values())
+        .filter(|((_, first_values), second_values)| first_values != second_values)
+        .map(|((key, _), _)| key);
+
+    let (operator, key) = differences.next()?;
+    if differences.next().is_some() || !operator.is_set_safe() {
+        return None;
+    }
+
+    let second_values = second
+        .iter()
+        .filter(|c| c.operator == *operator)
+        .flat_map(|c| c.conditions.iter())
+        .find(|c| c.key == *key)?
+        .values
+        .clone();
+
+    let mut merged = first.to_vec();
+    merged
+        .iter_mut()
+        .filter(|c| c.operator == *operator)
+        .flat_map(|c| c.conditions.iter_mut())
+        .find(|c| c.key == *key)?
+        .values
+        .merge(second_values);
+    Some(merged)
+}
+
+impl ConditionValue {
+    fn sort_key(&self) -> (u8, String) {
+        let kind = match self {
+            ConditionValue::String(_) => 0,
+            ConditionValue::Bool(_) => 1,
+            ConditionValue::Number(_) => 2,
+            ConditionValue::Null => 3,
+        };
+        (kind, self.to_string())
+    }
+}
+
+impl fmt::Display for ConditionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionValue::String(value) => f.write_str(value),
+            ConditionValue::Bool(value) => write!(f, "{}", value),
+            ConditionValue::Number(value) => write!(f, "{}", value),
+            ConditionValue::Null => f.write_str("null"),
         }
     }
 }
+
+impl From<String> for ConditionValue {
+    fn from(value: String) -> Self {
+        ConditionValue::String(value)
+    }
+}
+
+impl From<&str> for ConditionValue {
+    fn from(value: &str) -> Self {
+        ConditionValue::String(value.to_string())
+    }
+}
+
+impl From<bool> for ConditionValue {
+    fn from(value: bool) -> Self {
+        ConditionValue::Bool(value)
+    }
+}
+
+impl From<Number> for ConditionValue {
+    fn from(value: Number) -> Self {
+        ConditionValue::Number(value)
+    }
+}
@@ -1,19 +1,47 @@
-use serde::{ser::SerializeMap, Serialize};
+use std::collections::BTreeMap;
 
-use super::{Condition, ConditionStatement};
+use serde::{
+    ser::{Error, SerializeMap},
+    Serialize,
+};
+
+use super::{Condition, ConditionStatement, ConditionValue};
+
+/// Serializes the condition statements of a policy statement as the single
+/// `Condition` block IAM expects, with one entry per operator. Statements that
+/// share an operator are combined under that operator.
+pub fn serialize_conditions<S>(
+    condition_statements: &[ConditionStatement],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let mut operators: BTreeMap<String, Vec<&Condition>> = BTreeMap::new();
+    for condition_statement in condition_statements {
+        operators
+            .entry(condition_statement.operator.to_string())
+            .or_default()
+            .extend(condition_statement.conditions.iter());
+    }
+
+    let mut map = serializer.serialize_map(Some(operators.len()))?;
+    for (operator, conditions) in operators {
+        map.serialize_entry(&operator, &ConditionBlock::new(conditions))?;
+    }
+    map.end()
+}
 
 impl Serialize for ConditionStatement {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let mut map = serializer.serialize_map(None)?;
-        map.serialize_key(&self.operator)?;
-
-        self.conditions
-            .iter()
-            .for_each(|condition| map.serialize_value(condition).unwrap());
-
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(
+            &self.operator.to_string(),
+            &ConditionBlock::new(self.conditions.iter()),
+        )?;
         map.end()
     }
 }
@@ -23,17 +51,60 @@ impl Serialize for Condition {
     where
         S: serde::Serializer,
     {
-        let mut map = serializer.serialize_map(None)?;
-        map.serialize_key(&self.key)?;
-        if self.values.len() == 1 {
-            map.serialize_value(&self.values[0])?;
-        } else {
-            map.serialize_value(&self.values)?;
+        ConditionBlock::new([self]).serialize(serializer)
+    }
+}
+
+/// The condition keys and values below a single operator.
+struct ConditionBlock<'a>(Vec<&'a Condition>);
+
+impl<'a> ConditionBlock<'a> {
+    fn new(conditions: impl IntoIterator<Item = &'a Condition>) -> Self {
+        Self(conditions.into_iter().collect())
+    }
+}
+
+impl Serialize for ConditionBlock<'_> {
+    /// A JSON object cannot hold a key twice, and writing the conditions on a
+    /// repeated key as one would make alternatives of conditions that all have
+    /// to hold, so a repeated key is an error.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (index, condition) in self.0.iter().enumerate() {
+            if self.0[..index].iter().any(|c| c.key == condition.key) {
+                return Err(S::Error::custom(format!(
+                    "Condition key {} appears twice below the same operator",
+                    condition.key
+                )));
+            }
+            map.serialize_key(&condition.key)?;
+            if condition.values.len() == 1 {
+                map.serialize_value(&condition.values[0])?;
+            } else {
+                map.serialize_value(&condition.values)?;
+            }
         }
         map.end()
     }
 }
 
+impl Serialize for ConditionValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            ConditionValue::String(value) => serializer.serialize_str(value),
+            ConditionValue::Bool(value) => serializer.serialize_bool(*value),
+            ConditionValue::Number(value) => value.serialize(serializer),
+            ConditionValue::Null => serializer.serialize_unit(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,7 +112,7 @@ mod tests {
     #[test]
     fn test_serialize_condition_statement() {
         let condition_statement = ConditionStatement::new(
-            "StringEquals".to_string(),
+            "StringEquals".parse().unwrap(),
             vec![Condition::new(
                 "iam:AWSServiceName".to_string(),
                 vec![
@@ -66,7 +137,7 @@ mod tests {
     #[test]
     fn test_serialize_condition_one_value() {
         let condition_statement = ConditionStatement::new(
-            "StringEquals".to_string(),
+            "StringEquals".parse().unwrap(),
             vec![Condition::new(
                 "iam:AWSServiceName".to_string(),
                 vec!["autoscaling.amazonaws.com".to_string()],
@@ -80,4 +151,94 @@ mod tests {
             r#"{"StringEquals":{"iam:AWSServiceName":"autoscaling.amazonaws.com"}}"#
         );
     }
+
+    #[test]
+    fn test_serialize_typed_condition_values() {
+        let condition_statement = ConditionStatement::new(
+            "NumericLessThan".parse().unwrap(),
+            vec![Condition::new(
+                "s3:max-keys".to_string(),
+                vec![ConditionValue::Number(10.into())],
+            )],
+        );
+
+        let json = serde_json::to_string(&condition_statement).unwrap();
+
+        assert_eq!(json, r#"{"NumericLessThan":{"s3:max-keys":10}}"#);
+    }
+
+    #[test]
+    fn test_serialize_bool_condition_value() {
+        let condition_statement = ConditionStatement::new(
+            "Bool".parse().unwrap(),
+            vec![Condition::new(
+                "aws:SecureTransport".to_string(),
+                vec![false],
+            )],
+        );
+
+        let json = serde_json::to_string(&condition_statement).unwrap();
+
+        assert_eq!(json, r#"{"Bool":{"aws:SecureTransport":false}}"#);
+    }
+
+    #[test]
+    fn test_serialize_condition_statement_multiple_keys() {
+        let condition_statement = ConditionStatement::new(
+            "StringEquals".parse().unwrap(),
+            vec![
+                Condition::new("aws:PrincipalTag/team".to_string(), vec!["ops"]),
+                Condition::new(
+                    "aws:RequestedRegion".to_string(),
+                    vec!["eu-west-1", "eu-central-1"],
+                ),
+            ],
+        );
+
+        let json = serde_json::to_string(&condition_statement).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"StringEquals":{"aws:PrincipalTag/team":"ops","aws:RequestedRegion":["eu-west-1","eu-central-1"]}}"#
+        );
+    }
+
+    #[test]
+    fn test_serialize_conditions_multiple_operators() {
+        let condition_statements = vec![
+            ConditionStatement::new(
+                "StringEquals".parse().unwrap(),
+                vec![Condition::new(
+                    "aws:PrincipalTag/team".to_string(),
+                    vec!["ops"],
+                )],
+            ),
+            ConditionStatement::new(
+                "Bool".parse().unwrap(),
+                vec![Condition::new(
+                    "aws:SecureTransport".to_string(),
+                    vec![true],
+                )],
+            ),
+            ConditionStatement::new(
+                "StringEquals".parse().unwrap(),
+                vec![Condition::new(
+                    "aws:RequestedRegion".to_string(),
+                    vec!["eu-west-1"],
+                )],
+            ),
+        ];
+
+        let mut json = Vec::new();
+        serialize_conditions(
+            &condition_statements,
+            &mut serde_json::Serializer::new(&mut json),
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            r#"{"Bool":{"aws:SecureTransport":true},"StringEquals":{"aws:PrincipalTag/team":"ops","aws:RequestedRegion":"eu-west-1"}}"#
+        );
+    }
 }
@@ -0,0 +1,125 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::MaipError;
+
+use super::policy_statement::{is_conflict, PolicyStatement};
+
+/// What to do with an Allow and a Deny statement covering exactly the same
+/// actions, resources, principals and conditions.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum ConflictPolicy {
+    /// Keep both statements in the merged document.
+    KeepBoth,
+    /// Drop the Allow statement, which matches how IAM evaluates the statements.
+    #[default]
+    DenyWins,
+    /// Fail the merge.
+    Fail,
+}
+
+impl fmt::Display for ConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictPolicy::KeepBoth => f.write_str("keep-both"),
+            ConflictPolicy::DenyWins => f.write_str("deny-wins"),
+            ConflictPolicy::Fail => f.write_str("fail"),
+        }
+    }
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep-both" => Ok(ConflictPolicy::KeepBoth),
+            "deny-wins" => Ok(ConflictPolicy::DenyWins),
+            "fail" => Ok(ConflictPolicy::Fail),
+            _ => Err(format!(
+                "Unknown conflict policy: {}, expected keep-both, deny-wins or fail",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct StatementConflict {
+    pub allow: PolicyStatement,
+    pub deny: PolicyStatement,
+    pub resolution: ConflictPolicy,
+}
+
+impl fmt::Display for StatementConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let actions = if self.allow.action.is_empty() {
+            format!("all actions except {}", self.allow.not_action.join(", "))
+        } else {
+            self.allow.action.join(", ")
+        };
+        let resources = if self.allow.not_resource.is_empty() {
+            self.allow.resource.join(", ")
+        } else {
+            format!(
+                "all resources except {}",
+                self.allow.not_resource.join(", ")
+            )
+        };
+        let outcome = match self.resolution {
+            ConflictPolicy::KeepBoth => "kept both statements",
+            ConflictPolicy::DenyWins => "dropped the Allow statement",
+            ConflictPolicy::Fail => "merge failed",
+        };
+        write!(
+            f,
+            "Allow and Deny of {} on {} ({})",
+            actions, resources, outcome
+        )
+    }
+}
+
+/// Finds Allow and Deny statements that cancel each other out and resolves them
+/// according to the conflict policy. Returns every conflict found.
+pub fn resolve_conflicts(
+    statements: &mut Vec<PolicyStatement>,
+    conflict_policy: ConflictPolicy,
+) -> Result<Vec<StatementConflict>, MaipError> {
+    let mut conflicts = Vec::new();
+    let mut dropped = vec![false; statements.len()];
+    let mut absorbed = Vec::new();
+
+    for (allow_index, allow) in statements.iter().enumerate() {
+        if allow.effect != "Allow" {
+            continue;
+        }
+        let deny = statements
+            .iter()
+            .enumerate()
+            .find(|(_, deny)| deny.effect == "Deny" && is_conflict(allow, deny));
+
+        if let Some((deny_index, deny)) = deny {
+            let conflict = StatementConflict {
+                allow: allow.clone(),
+                deny: deny.clone(),
+                resolution: conflict_policy,
+            };
+            if conflict_policy == ConflictPolicy::Fail {
+                return Err(MaipError::MergeConflict(conflict.to_string()));
+            }
+            if conflict_policy == ConflictPolicy::DenyWins {
+                dropped[allow_index] = true;
+                absorbed.push((deny_index, allow.origin.clone()));
+            }
+            conflicts.push(conflict);
+        }
+    }
+
+    // The Deny statement stands for the Allow statements it replaces.
+    for (deny_index, origin) in absorbed {
+        statements[deny_index].origin.extend(origin);
+    }
+
+    let mut dropped = dropped.into_iter();
+    statements.retain(|_| !dropped.next().unwrap_or(false));
+    Ok(conflicts)
+}
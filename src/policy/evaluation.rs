@@ -0,0 +1,388 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use super::{
+    condition_operator::SetQualifier,
+    wildcard::{instances, resource_covers, string_like},
+    ConditionOperator, ConditionOperatorKind, ConditionValue,
+};
+
+/// The value of a condition key in a request.
+#[derive(Debug, PartialEq, Clone)]
+pub enum RequestValue {
+    /// The request has no value for the key.
+    Absent,
+    /// The values of the key, more than one only for multivalued keys.
+    Values(Vec<String>),
+}
+
+/// Returns true if a condition with the operator and values holds for the
+/// value of its key in the request.
+///
+/// A missing key satisfies `Null` conditions with the value `true`, `IfExists`
+/// and negated operators and `ForAllValues`, but no other condition. Without a
+/// set qualifier, a multivalued request matches if one of its values does.
+pub fn condition_holds(
+    operator: &ConditionOperator,
+    values: &[ConditionValue],
+    request: &RequestValue,
+) -> bool {
+    let values: Vec<String> = values.iter().filter_map(value_string).collect();
+    let request = match request {
+        RequestValue::Absent => {
+            return match (operator.kind, operator.qualifier) {
+                (ConditionOperatorKind::Null, _) => values.iter().any(|v| v == "true"),
+                (_, Some(SetQualifier::ForAllValues)) => true,
+                (_, Some(SetQualifier::ForAnyValue)) => false,
+                _ => operator.if_exists || operator.is_negated(),
+            }
+        }
+        RequestValue::Values(request) => request,
+    };
+    if operator.kind == ConditionOperatorKind::Null {
+        return values.iter().any(|v| v == "false");
+    }
+
+    let holds = |request: &String| {
+        let listed = values
+            .iter()
+            .any(|value| value_matches(operator.kind, value, request));
+        listed != operator.is_negated()
+    };
+    match operator.qualifier {
+        Some(SetQualifier::ForAllValues) => request.iter().all(holds),
+        _ => request.iter().any(holds),
+    }
+}
+
+/// Returns true if the request value matches the condition value, using the
+/// positive form of negated operators.
+fn value_matches(kind: ConditionOperatorKind, value: &str, request: &str) -> bool {
+    use ConditionOperatorKind::*;
+
+    match kind {
+        StringEquals | StringNotEquals | BinaryEquals => value == request,
+        StringEqualsIgnoreCase | StringNotEqualsIgnoreCase => value.eq_ignore_ascii_case(request),
+        StringLike | StringNotLike => string_like(value, request),
+        NumericEquals | NumericNotEquals => compare(value, request, parse_number, |r, v| r == v),
+        NumericLessThan => compare(value, request, parse_number, |r, v| r < v),
+        NumericLessThanEquals => compare(value, request, parse_number, |r, v| r <= v),
+        NumericGreaterThan => compare(value, request, parse_number, |r, v| r > v),
+        NumericGreaterThanEquals => compare(value, request, parse_number, |r, v| r >= v),
+        DateEquals | DateNotEquals => compare(value, request, parse_date, |r, v| r == v),
+        DateLessThan => compare(value, request, parse_date, |r, v| r < v),
+        DateLessThanEquals => compare(value, request, parse_date, |r, v| r <= v),
+        DateGreaterThan => compare(value, request, parse_date, |r, v| r > v),
+        DateGreaterThanEquals => compare(value, request, parse_date, |r, v| r >= v),
+        Bool => value.eq_ignore_ascii_case(request),
+        IpAddress | NotIpAddress => match (parse_cidr(value), parse_cidr(request)) {
+            (Some(network), Some(address)) => network.contains(address.address),
+            _ => false,
+        },
+        ArnEquals | ArnLike | ArnNotEquals | ArnNotLike => resource_covers(value, request),
+        Null => false,
+    }
+}
+
+fn compare(
+    value: &str,
+    request: &str,
+    parse: fn(&str) -> Option<f64>,
+    holds: fn(f64, f64) -> bool,
+) -> bool {
+    match (parse(value), parse(request)) {
+        (Some(value), Some(request)) => holds(request, value),
+        _ => false,
+    }
+}
+
+/// Request values that tell apart every combination of the conditions on one
+/// key: the key missing, every listed value, instances of `Like` and ARN
+/// patterns, values on both sides of every numeric, date and address bound
+/// and a value not used by any condition. Keys of set operators also get
+/// every pair of listed values.
+pub fn request_values(conditions: &[(ConditionOperator, &[ConditionValue])]) -> Vec<RequestValue> {
+    use ConditionOperatorKind::*;
+
+    let mut listed: Vec<String> = Vec::new();
+    let mut singles: Vec<String> = Vec::new();
+    let mut numbers: Vec<f64> = Vec::new();
+    let mut dates: Vec<f64> = Vec::new();
+    for (operator, values) in conditions {
+        for value in values.iter().filter_map(value_string) {
+            match operator.kind {
+                StringEqualsIgnoreCase | StringNotEqualsIgnoreCase => {
+                    singles.push(value.to_uppercase());
+                    singles.push(value.to_lowercase());
+                }
+                StringLike | StringNotLike | ArnEquals | ArnLike | ArnNotEquals | ArnNotLike => {
+                    singles.extend(instances(&value));
+                }
+                NumericEquals
+                | NumericNotEquals
+                | NumericLessThan
+                | NumericLessThanEquals
+                | NumericGreaterThan
+                | NumericGreaterThanEquals => {
+                    numbers.extend(parse_number(&value));
+                }
+                DateEquals
+                | DateNotEquals
+                | DateLessThan
+                | DateLessThanEquals
+                | DateGreaterThan
+                | DateGreaterThanEquals => dates.extend(parse_date(&value)),
+                IpAddress | NotIpAddress => {
+                    singles.extend(parse_cidr(&value).into_iter().flat_map(Network::bounds));
+                }
+                Bool => singles.extend(["true".to_string(), "false".to_string()]),
+                StringEquals | StringNotEquals | BinaryEquals | Null => {}
+            }
+            if operator.kind != Null {
+                listed.push(value);
+            }
+        }
+    }
+    singles.extend(around(numbers));
+    singles.extend(around(dates));
+    singles.push("maip-other".to_string());
+
+    let mut candidates = vec![RequestValue::Absent];
+    for value in listed.iter().chain(singles.iter()) {
+        let candidate = RequestValue::Values(vec![value.clone()]);
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    if conditions
+        .iter()
+        .any(|(operator, _)| operator.qualifier.is_some())
+    {
+        for (index, first) in listed.iter().enumerate() {
+            for second in listed[index + 1..].iter().filter(|second| *second != first) {
+                let candidate = RequestValue::Values(vec![first.clone(), second.clone()]);
+                if !candidates.contains(&candidate) {
+                    candidates.push(candidate);
+                }
+            }
+        }
+    }
+    candidates
+}
+
+/// The bounds, the values between them and one value below and above all.
+fn around(mut bounds: Vec<f64>) -> Vec<String> {
+    bounds.sort_by(f64::total_cmp);
+    bounds.dedup();
+    let (Some(first), Some(last)) = (bounds.first(), bounds.last()) else {
+        return Vec::new();
+    };
+    let mut values = vec![first - 1.0, last + 1.0];
+    values.extend(bounds.windows(2).map(|pair| (pair[0] + pair[1]) / 2.0));
+    values.extend(bounds.iter());
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn value_string(value: &ConditionValue) -> Option<String> {
+    match value {
+        ConditionValue::String(value) => Some(value.clone()),
+        ConditionValue::Bool(value) => Some(value.to_string()),
+        ConditionValue::Number(value) => Some(value.to_string()),
+        ConditionValue::Null => None,
+    }
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value.parse().ok()
+}
+
+/// Parses a date as seconds since the epoch, either given as such or in the
+/// ISO 8601 forms `2024-01-31`, `2024-01-31T12:00:00Z` and
+/// `2024-01-31T12:00:00+01:00`, with optional fractions of a second.
+fn parse_date(value: &str) -> Option<f64> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some(seconds);
+    }
+
+    let (date, time) = value.split_once('T').unwrap_or((value, "00:00:00Z"));
+    let mut date = date.splitn(3, '-');
+    let year: i64 = date.next()?.parse().ok()?;
+    let month: i64 = date.next()?.parse().ok()?;
+    let day: i64 = date.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let (clock, offset) = match time.strip_suffix('Z') {
+        Some(clock) => (clock, 0.0),
+        None => {
+            let index = time.rfind(['+', '-']).unwrap_or(time.len());
+            let offset = match time[index..].split_once(':') {
+                Some((hours, minutes)) => {
+                    hours[1..].parse::<f64>().ok()? * 3600.0 + minutes.parse::<f64>().ok()? * 60.0
+                }
+                None if index == time.len() => 0.0,
+                None => return None,
+            };
+            match time[index..].starts_with('-') {
+                true => (&time[..index], -offset),
+                false => (&time[..index], offset),
+            }
+        }
+    };
+    let mut clock = clock.splitn(3, ':');
+    let hours: f64 = clock.next()?.parse().ok()?;
+    let minutes: f64 = clock.next()?.parse().ok()?;
+    let seconds: f64 = clock.next().unwrap_or("0").parse().ok()?;
+
+    let days = days_from_civil(year, month, day) as f64;
+    Some(days * 86400.0 + hours * 3600.0 + minutes * 60.0 + seconds - offset)
+}
+
+/// The number of days since 1970-01-01 of a date in the proleptic Gregorian
+/// calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// An IPv4 or IPv6 network in CIDR notation, a single address without a
+/// prefix length.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Network {
+    address: u128,
+    prefix: u32,
+    bits: u32,
+}
+
+impl Network {
+    fn contains(&self, address: u128) -> bool {
+        let shift = self.bits - self.prefix;
+        address.checked_shr(shift) == self.address.checked_shr(shift)
+    }
+
+    /// The first address of the network and the addresses right before and
+    /// after it.
+    fn bounds(self) -> Vec<String> {
+        let shift = self.bits - self.prefix;
+        let host = 1u128.checked_shl(shift).map_or(u128::MAX, |size| size - 1);
+        let first = self.address & !host;
+        let max = 1u128
+            .checked_shl(self.bits)
+            .map_or(u128::MAX, |size| size - 1);
+        let mut addresses = vec![first];
+        addresses.extend(first.checked_sub(1));
+        addresses.extend((first | host).checked_add(1).filter(|next| *next <= max));
+        addresses
+            .into_iter()
+            .map(|address| match self.bits {
+                32 => Ipv4Addr::from(address as u32).to_string(),
+                _ => Ipv6Addr::from(address).to_string(),
+            })
+            .collect()
+    }
+}
+
+fn parse_cidr(value: &str) -> Option<Network> {
+    let (address, prefix) = match value.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix.parse::<u32>().ok()?)),
+        None => (value, None),
+    };
+    let (address, bits) = match address.parse::<IpAddr>().ok()? {
+        IpAddr::V4(address) => (u32::from(address) as u128, 32),
+        IpAddr::V6(address) => (u128::from(address), 128),
+    };
+    let prefix = prefix.unwrap_or(bits);
+    if prefix > bits {
+        return None;
+    }
+    Some(Network {
+        address,
+        prefix,
+        bits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holds(operator: &str, values: &[&str], request: &[&str]) -> bool {
+        let values: Vec<ConditionValue> = values
+            .iter()
+            .map(|value| ConditionValue::String(value.to_string()))
+            .collect();
+        let request = match request {
+            [] => RequestValue::Absent,
+            values => RequestValue::Values(values.iter().map(|value| value.to_string()).collect()),
+        };
+        condition_holds(&operator.parse().unwrap(), &values, &request)
+    }
+
+    #[test]
+    fn test_missing_keys() {
+        assert!(!holds("StringEquals", &["a"], &[]));
+        assert!(holds("StringEqualsIfExists", &["a"], &[]));
+        assert!(holds("StringNotEquals", &["a"], &[]));
+        assert!(holds("ForAllValues:StringEquals", &["a"], &[]));
+        assert!(!holds("ForAnyValue:StringNotEquals", &["a"], &[]));
+        assert!(holds("Null", &["true"], &[]));
+        assert!(!holds("Null", &["true"], &["a"]));
+    }
+
+    #[test]
+    fn test_operators() {
+        assert!(holds("StringLike", &["vpc-*"], &["vpc-1"]));
+        assert!(!holds("StringNotLike", &["vpc-*"], &["vpc-1"]));
+        assert!(holds("StringEqualsIgnoreCase", &["Prod"], &["PROD"]));
+        assert!(holds("NumericLessThan", &["10"], &["9.5"]));
+        assert!(!holds("NumericLessThan", &["10"], &["10"]));
+        assert!(holds(
+            "DateGreaterThan",
+            &["2024-01-01T00:00:00Z"],
+            &["2024-01-01T01:00:00+00:30"]
+        ));
+        assert!(holds("IpAddress", &["10.0.0.0/8"], &["10.1.2.3"]));
+        assert!(holds("NotIpAddress", &["10.0.0.0/8"], &["11.0.0.0"]));
+        assert!(holds(
+            "ArnLike",
+            &["arn:aws:iam::*:role/admin"],
+            &["arn:aws:iam::123456789012:role/admin"]
+        ));
+    }
+
+    #[test]
+    fn test_set_qualifiers() {
+        assert!(holds("ForAnyValue:StringEquals", &["a"], &["a", "b"]));
+        assert!(!holds("ForAllValues:StringEquals", &["a"], &["a", "b"]));
+        assert!(holds("ForAllValues:StringEquals", &["a", "b"], &["a", "b"]));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-02"), Some(86400.0));
+        assert_eq!(parse_date("2024-02-29T12:00:00Z"), Some(1709208000.0));
+        assert_eq!(parse_date("1709208000"), Some(1709208000.0));
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn test_request_values_separate_bounds() {
+        let values = [ConditionValue::String("10".to_string())];
+        let operator = ConditionOperator::new(ConditionOperatorKind::NumericLessThan);
+
+        let candidates = request_values(&[(operator, &values)]);
+
+        assert!(candidates
+            .iter()
+            .any(|candidate| condition_holds(&operator, &values, candidate)));
+        assert!(candidates
+            .iter()
+            .any(|candidate| !condition_holds(&operator, &values, candidate)));
+        assert!(candidates.contains(&RequestValue::Values(vec!["11".to_string()])));
+        assert!(candidates.contains(&RequestValue::Values(vec!["9".to_string()])));
+    }
+}
@@ -2,12 +2,12 @@ pub trait Merge {
     fn merge(&mut self, other: Self);
 }
 
-impl Merge for Vec<String> {
+impl<T: PartialEq> Merge for Vec<T> {
     fn merge(&mut self, other: Self) {
-        other.iter().for_each(|x| {
-            if !self.contains(x) {
-                self.push(x.clone());
+        other.into_iter().for_each(|x| {
+            if !self.contains(&x) {
+                self.push(x);
             }
         });
     }
-}
\ No newline at end of file
+}
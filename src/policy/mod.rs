@@ -1,18 +1,66 @@
+mod condition_operator;
 mod condition_statement;
 mod condition_statement_serde;
+mod conflict;
+mod evaluation;
 mod merge;
+mod optimize;
+mod pack;
 mod policy_document;
 mod policy_statement;
 mod policy_statement_serde;
+mod principal;
+mod principal_serde;
+mod split;
+mod subsumption;
+mod target;
+mod verify;
+mod wildcard;
 
 pub use policy_document::merge_policy_documents;
+pub use policy_document::merge_policy_documents_with_options;
 pub use policy_document::policy_from_arn;
 pub use policy_document::policy_from_file;
 pub use policy_document::policy_to_file;
+pub use policy_document::MergeOptions;
+pub use policy_document::MergeOutcome;
 pub use policy_document::PolicyDocument;
+pub use policy_document::VERSION_2008_10_17;
+pub use policy_document::VERSION_2012_10_17;
 
+pub use policy_statement::is_conflict;
 pub use policy_statement::merge_statements;
 pub use policy_statement::PolicyStatement;
 
+pub use condition_operator::ConditionOperator;
+pub use condition_operator::ConditionOperatorKind;
+pub use condition_operator::SetQualifier;
+
 pub use condition_statement::Condition;
 pub use condition_statement::ConditionStatement;
+pub use condition_statement::ConditionValue;
+
+pub use conflict::ConflictPolicy;
+pub use conflict::StatementConflict;
+
+pub use optimize::MergeStrategy;
+
+pub use pack::pack_policy_documents;
+pub use pack::pack_policy_documents_with;
+pub use pack::PackOutcome;
+
+pub use split::split_policy_document;
+
+pub use target::PolicyTarget;
+
+pub use verify::verify_equivalence;
+pub use verify::Counterexample;
+pub use verify::Decision;
+pub use verify::VerifyError;
+pub use verify::MAX_REQUESTS;
+
+pub use wildcard::action_covers;
+pub use wildcard::resource_covers;
+
+pub use principal::Principal;
+pub use principal::PrincipalType;
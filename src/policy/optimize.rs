@@ -0,0 +1,291 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    str::FromStr,
+};
+
+use super::{
+    condition_statement::same_conditions,
+    policy_document::{json_size, merge_statement_list},
+    policy_statement::{merge_sid, PolicyStatement},
+};
+
+/// How the statements of the merged documents are combined.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum MergeStrategy {
+    /// Merge every statement into the first statement it can be merged with.
+    /// Fast, but the result depends on the order of the input.
+    #[default]
+    Greedy,
+    /// Regroup the action and resource pairs of statements with the same
+    /// effect, principals and conditions to minimise the serialized size.
+    Optimal,
+}
+
+impl fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeStrategy::Greedy => f.write_str("greedy"),
+            MergeStrategy::Optimal => f.write_str("optimal"),
+        }
+    }
+}
+
+impl FromStr for MergeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "greedy" => Ok(MergeStrategy::Greedy),
+            "optimal" => Ok(MergeStrategy::Optimal),
+            _ => Err(format!(
+                "Unknown merge strategy: {}, expected greedy or optimal",
+                s
+            )),
+        }
+    }
+}
+
+/// Regroups statements with the same effect, principals and conditions.
+///
+/// Every group is flattened into the action and resource pairs it grants. The
+/// pairs are then grouped once by actions sharing the same resources and once
+/// by resources sharing the same actions, and the smallest of both and the
+/// greedy merge of the group is kept. Statements using NotAction, NotResource
+/// or NotPrincipal, or without resources, are left unchanged.
+pub fn optimize_statements(statements: Vec<PolicyStatement>) -> Vec<PolicyStatement> {
+    let mut groups: Vec<Vec<PolicyStatement>> = Vec::new();
+    let mut unchanged = Vec::new();
+
+    for statement in statements {
+        if !can_regroup(&statement) {
+            unchanged.push(statement);
+            continue;
+        }
+        match groups
+            .iter_mut()
+            .find(|group| same_group(&group[0], &statement))
+        {
+            Some(group) => group.push(statement),
+            None => groups.push(vec![statement]),
+        }
+    }
+
+    let mut optimized: Vec<PolicyStatement> =
+        groups.iter().flat_map(|g| optimize_group(g)).collect();
+    optimized.extend(unchanged);
+    optimized
+}
+
+fn can_regroup(statement: &PolicyStatement) -> bool {
+    statement.not_principal.is_none()
+        && statement.not_action.is_empty()
+        && statement.not_resource.is_empty()
+        && !statement.action.is_empty()
+        && !statement.resource.is_empty()
+}
+
+fn same_group(first_statement: &PolicyStatement, second_statement: &PolicyStatement) -> bool {
+    first_statement.effect == second_statement.effect
+        && first_statement.principal == second_statement.principal
+        && same_conditions(&first_statement.condition, &second_statement.condition)
+}
+
+/// The pairs granted by a group, keyed by the lowercase action since actions
+/// are case-insensitive. Each pair remembers the statements it came from.
+struct Pairs {
+    actions: BTreeMap<String, String>,
+    sources: BTreeMap<(String, String), BTreeSet<usize>>,
+}
+
+impl Pairs {
+    fn new(group: &[PolicyStatement]) -> Self {
+        let mut actions = BTreeMap::new();
+        let mut sources: BTreeMap<(String, String), BTreeSet<usize>> = BTreeMap::new();
+
+        for (index, statement) in group.iter().enumerate() {
+            for action in statement.action.iter() {
+                let key = action.to_lowercase();
+                actions.entry(key.clone()).or_insert_with(|| action.clone());
+                for resource in statement.resource.iter() {
+                    sources
+                        .entry((key.clone(), resource.clone()))
+                        .or_default()
+                        .insert(index);
+                }
+            }
+        }
+
+        Self { actions, sources }
+    }
+
+    /// Groups actions with identical resource sets into one statement each.
+    fn by_action(&self) -> Vec<(Vec<String>, Vec<String>)> {
+        let mut resources: BTreeMap<&String, BTreeSet<&String>> = BTreeMap::new();
+        for (action, resource) in self.sources.keys() {
+            resources.entry(action).or_default().insert(resource);
+        }
+
+        let mut grouped: BTreeMap<BTreeSet<&String>, Vec<String>> = BTreeMap::new();
+        for (action, resources) in resources {
+            grouped
+                .entry(resources)
+                .or_default()
+                .push(self.actions[action].clone());
+        }
+
+        grouped
+            .into_iter()
+            .map(|(resources, actions)| (actions, resources.into_iter().cloned().collect()))
+            .collect()
+    }
+
+    /// Groups resources with identical action sets into one statement each.
+    fn by_resource(&self) -> Vec<(Vec<String>, Vec<String>)> {
+        let mut actions: BTreeMap<&String, BTreeSet<&String>> = BTreeMap::new();
+        for (action, resource) in self.sources.keys() {
+            actions.entry(resource).or_default().insert(action);
+        }
+
+        let mut grouped: BTreeMap<BTreeSet<&String>, Vec<String>> = BTreeMap::new();
+        for (resource, actions) in actions {
+            grouped.entry(actions).or_default().push(resource.clone());
+        }
+
+        grouped
+            .into_iter()
+            .map(|(actions, resources)| {
+                let actions = actions
+                    .into_iter()
+                    .map(|action| self.actions[action].clone())
+                    .collect();
+                (actions, resources)
+            })
+            .collect()
+    }
+
+    /// The statements contributing a pair to the given lists.
+    fn contributors<'a>(
+        &self,
+        group: &'a [PolicyStatement],
+        actions: &[String],
+        resources: &[String],
+    ) -> Vec<&'a PolicyStatement> {
+        let mut indices: BTreeSet<usize> = BTreeSet::new();
+        for action in actions {
+            for resource in resources {
+                if let Some(sources) = self.sources.get(&(action.to_lowercase(), resource.clone()))
+                {
+                    indices.extend(sources);
+                }
+            }
+        }
+        indices.into_iter().map(|index| &group[index]).collect()
+    }
+}
+
+fn optimize_group(group: &[PolicyStatement]) -> Vec<PolicyStatement> {
+    if group.len() < 2 {
+        return group.to_vec();
+    }
+
+    let pairs = Pairs::new(group);
+    let to_statements = |lists: Vec<(Vec<String>, Vec<String>)>| {
+        lists
+            .into_iter()
+            .map(|(actions, resources)| {
+                let contributors = pairs.contributors(group, &actions, &resources);
+                let mut statement = group[0].clone();
+                // The Sid shared by all contributing statements.
+                statement.sid = contributors
+                    .iter()
+                    .map(|contributor| contributor.sid.clone())
+                    .reduce(|sid, other| merge_sid(&sid, &other))
+                    .flatten();
+                statement.origin = contributors
+                    .iter()
+                    .flat_map(|contributor| contributor.origin.iter().copied())
+                    .collect();
+                statement.action = actions;
+                statement.resource = resources;
+                statement.sort();
+                statement
+            })
+            .collect::<Vec<PolicyStatement>>()
+    };
+
+    [
+        to_statements(pairs.by_action()),
+        to_statements(pairs.by_resource()),
+        merge_statement_list(group),
+    ]
+    .into_iter()
+    .min_by_key(json_size)
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::allow;
+
+    #[test]
+    fn test_merge_strategy_from_str() {
+        assert_eq!("greedy".parse(), Ok(MergeStrategy::Greedy));
+        assert_eq!("optimal".parse(), Ok(MergeStrategy::Optimal));
+        assert!("fast".parse::<MergeStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_optimize_groups_actions_by_resources() {
+        let statements = vec![
+            allow(&["s3:GetObject"], &["arn:aws:s3:::a/*"]),
+            allow(&["s3:PutObject"], &["arn:aws:s3:::b/*"]),
+            allow(&["s3:GetObject"], &["arn:aws:s3:::b/*"]),
+            allow(&["s3:PutObject"], &["arn:aws:s3:::a/*"]),
+        ];
+
+        let optimized = optimize_statements(statements);
+
+        assert_eq!(
+            optimized,
+            vec![allow(
+                &["s3:GetObject", "s3:PutObject"],
+                &["arn:aws:s3:::a/*", "arn:aws:s3:::b/*"]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_optimize_keeps_sid_shared_by_contributing_statements() {
+        let statements = vec![
+            allow(&["s3:GetObject"], &["arn:aws:s3:::a/*"]).with_sid("Objects"),
+            allow(&["s3:GetObject"], &["arn:aws:s3:::b/*"]).with_sid("Objects"),
+            allow(&["ec2:DescribeInstances"], &["*"]).with_sid("Third"),
+        ];
+
+        let optimized = optimize_statements(statements);
+
+        assert_eq!(
+            optimized,
+            vec![
+                allow(&["ec2:DescribeInstances"], &["*"]).with_sid("Third"),
+                allow(&["s3:GetObject"], &["arn:aws:s3:::a/*", "arn:aws:s3:::b/*"])
+                    .with_sid("Objects"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_optimize_leaves_not_action_statements() {
+        let not_action = allow(&[], &["*"]).with_not_action(vec!["iam:*".to_string()]);
+        let statements = vec![not_action.clone(), allow(&["s3:GetObject"], &["*"])];
+
+        let optimized = optimize_statements(statements);
+
+        assert_eq!(
+            optimized,
+            vec![allow(&["s3:GetObject"], &["*"]), not_action]
+        );
+    }
+}
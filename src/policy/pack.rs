@@ -0,0 +1,240 @@
+use crate::error::MaipError;
+
+use super::{
+    merge_policy_documents_with_options, split_policy_document, MergeOptions, PolicyDocument,
+    StatementConflict,
+};
+
+#[derive(Debug, PartialEq)]
+pub struct PackOutcome {
+    pub documents: Vec<PolicyDocument>,
+    /// The indices of the packed documents holding the statements of each
+    /// input document, in the order of the input.
+    pub placements: Vec<Vec<usize>>,
+    pub conflicts: Vec<StatementConflict>,
+}
+
+/// Merges the documents into at most `max_policies` documents that each fit
+/// into `limit`.
+///
+/// If the merge of all documents is too large, two packings are tried and the
+/// one with fewer documents is kept, preferring the first:
+///
+/// - The input documents are packed first fit decreasing, each merged into
+///   the first bin that still fits into `limit` after the merge. An input
+///   document that is too large on its own is split into parts first.
+/// - The merge of all documents is split by statements. Every input document
+///   is placed in the documents holding statements built from its statements.
+///
+/// Conflicts are detected across all input documents, even if the conflicting
+/// statements end up in different documents.
+pub fn pack_policy_documents(
+    documents: &[PolicyDocument],
+    max_policies: usize,
+    limit: usize,
+    options: &MergeOptions,
+) -> Result<PackOutcome, MaipError> {
+    pack_policy_documents_with(
+        documents,
+        max_policies,
+        limit,
+        options,
+        &PolicyDocument::clone,
+    )
+}
+
+/// Like [`pack_policy_documents`], but rewrites every merged document with
+/// `rewrite` before it is reduced and measured, so that a rewrite making
+/// documents smaller, like compressing their actions, helps them fit.
+pub fn pack_policy_documents_with(
+    documents: &[PolicyDocument],
+    max_policies: usize,
+    limit: usize,
+    options: &MergeOptions,
+    rewrite: &dyn Fn(&PolicyDocument) -> PolicyDocument,
+) -> Result<PackOutcome, MaipError> {
+    let outcome = merge_policy_documents_with_options(documents, options)?;
+    let mut merged = rewrite(&outcome.document);
+    merged.reduce();
+    merged.sort();
+
+    let (packed, placements) = if merged.size() <= limit {
+        (vec![merged], vec![vec![0]; documents.len()])
+    } else {
+        let by_document = pack_by_document(documents, limit, options, rewrite)?;
+        let by_statement = split_policy_document(&merged, limit)?;
+        if by_statement.len() < by_document.0.len() {
+            let placements = statement_placements(documents, &by_statement);
+            (by_statement, placements)
+        } else {
+            by_document
+        }
+    };
+
+    if packed.len() > max_policies {
+        return Err(MaipError::PackingFailed {
+            required: packed.len(),
+            max_policies,
+            limit,
+        });
+    }
+
+    Ok(PackOutcome {
+        documents: packed,
+        placements,
+        conflicts: outcome.conflicts,
+    })
+}
+
+fn pack_by_document(
+    documents: &[PolicyDocument],
+    limit: usize,
+    options: &MergeOptions,
+    rewrite: &dyn Fn(&PolicyDocument) -> PolicyDocument,
+) -> Result<(Vec<PolicyDocument>, Vec<Vec<usize>>), MaipError> {
+    let mut parts = Vec::new();
+    for (index, document) in documents.iter().enumerate() {
+        let merged = merge_bin(std::slice::from_ref(document), options, rewrite)?;
+        for part in split_policy_document(&merged, limit)? {
+            parts.push((index, part));
+        }
+    }
+    parts.sort_by_cached_key(|(_, part)| std::cmp::Reverse(part.size()));
+
+    // Every part is merged into the document of a bin, instead of merging all
+    // parts of the bin again.
+    let mut bins: Vec<PolicyDocument> = Vec::new();
+    let mut placements = vec![Vec::new(); documents.len()];
+    for (index, part) in parts {
+        let mut placed = false;
+        for (bin_index, bin) in bins.iter_mut().enumerate() {
+            let candidate = merge_bin(&[bin.clone(), part.clone()], options, rewrite)?;
+            if candidate.size() <= limit {
+                *bin = candidate;
+                placements[index].push(bin_index);
+                placed = true;
+                break;
+            }
+        }
+        if !placed {
+            placements[index].push(bins.len());
+            bins.push(part);
+        }
+    }
+
+    placements.iter_mut().for_each(|p| {
+        p.sort();
+        p.dedup();
+    });
+    Ok((bins, placements))
+}
+
+/// An input document is placed in every packed document that holds a
+/// statement built from one of its statements.
+fn statement_placements(
+    documents: &[PolicyDocument],
+    packed: &[PolicyDocument],
+) -> Vec<Vec<usize>> {
+    (0..documents.len())
+        .map(|index| {
+            packed
+                .iter()
+                .enumerate()
+                .filter(|(_, document)| {
+                    document
+                        .statement
+                        .iter()
+                        .any(|statement| statement.origin.contains(&index))
+                })
+                .map(|(bin_index, _)| bin_index)
+                .collect()
+        })
+        .collect()
+}
+
+fn merge_bin(
+    documents: &[PolicyDocument],
+    options: &MergeOptions,
+    rewrite: &dyn Fn(&PolicyDocument) -> PolicyDocument,
+) -> Result<PolicyDocument, MaipError> {
+    let mut document = rewrite(&merge_policy_documents_with_options(documents, options)?.document);
+    document.reduce();
+    document.sort();
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{allow, document};
+
+    fn objects(bucket: &str, count: usize) -> PolicyDocument {
+        let resources: Vec<String> = (0..count)
+            .map(|i| format!("arn:aws:s3:::{}/object-{}", bucket, i))
+            .collect();
+        let resources: Vec<&str> = resources.iter().map(String::as_str).collect();
+        document(vec![allow(&["s3:GetObject", "s3:PutObject"], &resources)])
+    }
+
+    #[test]
+    fn test_pack_into_single_document() {
+        let documents = [objects("first", 2), objects("second", 2)];
+
+        let outcome = pack_policy_documents(&documents, 1, 6144, &MergeOptions::default()).unwrap();
+
+        assert_eq!(outcome.documents.len(), 1);
+        assert_eq!(outcome.placements, vec![vec![0], vec![0]]);
+    }
+
+    #[test]
+    fn test_pack_into_several_documents() {
+        let documents = [
+            objects("first", 10),
+            objects("second", 10),
+            objects("third", 10),
+        ];
+        let limit = merge_bin(
+            &documents[..2],
+            &MergeOptions::default(),
+            &PolicyDocument::clone,
+        )
+        .unwrap()
+        .size();
+
+        let outcome =
+            pack_policy_documents(&documents, 2, limit, &MergeOptions::default()).unwrap();
+
+        assert_eq!(outcome.documents.len(), 2);
+        assert!(outcome.documents.iter().all(|d| d.size() <= limit));
+        assert_eq!(outcome.placements, vec![vec![0], vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn test_pack_splits_large_input() {
+        let documents = [objects("first", 100)];
+        let limit = objects("first", 60).size();
+
+        let outcome =
+            pack_policy_documents(&documents, 2, limit, &MergeOptions::default()).unwrap();
+
+        assert_eq!(outcome.documents.len(), 2);
+        assert_eq!(outcome.placements, vec![vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_pack_fails_with_too_few_policies() {
+        let documents = [objects("first", 10), objects("other", 10)];
+        let limit = documents[0].size();
+
+        let result = pack_policy_documents(&documents, 1, limit, &MergeOptions::default());
+
+        assert!(matches!(
+            result,
+            Err(MaipError::PackingFailed {
+                required: 2,
+                max_policies: 1,
+                ..
+            })
+        ));
+    }
+}
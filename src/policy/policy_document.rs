@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeSet,
     fs::File,
     io::{BufReader, BufWriter},
 };
@@ -7,65 +8,245 @@ use serde::{Deserialize, Serialize};
 use serde_json::{from_reader, to_writer_pretty};
 use tokio::runtime::Builder;
 
-use super::policy_statement::{merge_statements, PolicyStatement};
+use crate::error::MaipError;
+use crate::json_string_or_vec::object_or_seq;
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+use super::condition_statement::{compare_conditions, ConditionValue};
+use super::conflict::{resolve_conflicts, ConflictPolicy, StatementConflict};
+use super::optimize::{optimize_statements, MergeStrategy};
+use super::policy_statement::{make_sids_unique, merge_statements, PolicyStatement};
+use super::subsumption::remove_subsumed_statements;
+
+pub const VERSION_2012_10_17: &str = "2012-10-17";
+pub const VERSION_2008_10_17: &str = "2008-10-17";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct PolicyDocument {
-    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    #[serde(deserialize_with = "object_or_seq")]
     pub statement: Vec<PolicyStatement>,
 }
 
 impl PolicyDocument {
     pub fn new(version: String, statement: Vec<PolicyStatement>) -> Self {
-        Self { version, statement }
+        Self {
+            version: Some(version),
+            id: None,
+            statement,
+        }
+    }
+
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// The version IAM evaluates the document with. A document without a
+    /// `Version` element is treated as `2008-10-17`.
+    pub fn effective_version(&self) -> &str {
+        self.version.as_deref().unwrap_or(VERSION_2008_10_17)
+    }
+
+    /// Returns true if any Resource or Condition value contains `${`, which is
+    /// a policy variable in `2012-10-17` but a literal string in `2008-10-17`.
+    pub fn has_policy_variables(&self) -> bool {
+        self.statement.iter().any(|statement| {
+            statement
+                .resource
+                .iter()
+                .chain(statement.not_resource.iter())
+                .map(|value| value.as_str())
+                .chain(
+                    statement
+                        .condition
+                        .iter()
+                        .flat_map(|c| c.conditions.iter())
+                        .flat_map(|c| c.values.iter())
+                        .filter_map(|v| v.as_str()),
+                )
+                .any(|value| value.contains("${"))
+        })
+    }
+
+    /// Upgrades a `2008-10-17` document to `2012-10-17`.
+    ///
+    /// Strings that would be read as policy variables after the upgrade are
+    /// escaped with `${$}` so they keep their literal meaning. A warning is
+    /// returned for every escaped string.
+    pub fn upgrade(&mut self) -> Result<Vec<String>, MaipError> {
+        match self.effective_version() {
+            VERSION_2012_10_17 => return Ok(Vec::new()),
+            VERSION_2008_10_17 => {}
+            version => return Err(MaipError::UnsupportedVersion(version.to_string())),
+        }
+
+        let mut warnings = Vec::new();
+        for (index, statement) in self.statement.iter_mut().enumerate() {
+            for value in variable_fields(statement) {
+                let escaped = value.replace("${", "${$}{");
+                warnings.push(format!(
+                    "Statement {}: \"{}\" would be a policy variable in version {}, escaped as \"{}\"",
+                    index, value, VERSION_2012_10_17, escaped
+                ));
+                *value = escaped;
+            }
+        }
+
+        self.version = Some(VERSION_2012_10_17.to_string());
+        Ok(warnings)
     }
 
+    /// The size AWS compares against the policy size limits, the number of
+    /// characters of the document without whitespace.
     pub fn size(&self) -> usize {
-        serde_json::to_string(self).unwrap().len()
+        json_size(self)
     }
 
     pub fn sort(&mut self) {
-        self.statement
-            .sort_by(|a, b| a.effect.cmp(&b.effect).then(a.action.cmp(&b.action)));
+        self.statement.iter_mut().for_each(|a| a.sort());
 
-        self.statement
-            .iter_mut()
-            .for_each(|a| a.action.sort_by_key(|a| a.to_lowercase()));
+        self.statement.sort_by(|a, b| {
+            a.effect
+                .cmp(&b.effect)
+                .then(a.action.cmp(&b.action))
+                .then(a.not_action.cmp(&b.not_action))
+                .then(a.resource.cmp(&b.resource))
+                .then(a.not_resource.cmp(&b.not_resource))
+                .then_with(|| compare_conditions(&a.condition, &b.condition))
+        });
     }
 
+    /// Removes actions covered by a wildcard action of the same statement and
+    /// everything that is already granted by another Allow statement.
     pub fn reduce(&mut self) {
         self.statement.iter_mut().for_each(|s| s.reduce());
+        remove_subsumed_statements(&mut self.statement);
     }
 }
 
-pub fn merge_policy_documents(documents: &[PolicyDocument]) -> Result<PolicyDocument, String> {
-    if documents.iter().any(|d| d.version != "2012-10-17") {
-        return Err("Only version 2012-10-17 is supported".to_string());
+/// Counts the characters of the JSON form of a value, ignoring whitespace.
+pub fn json_size<T: Serialize + ?Sized>(value: &T) -> usize {
+    serde_json::to_string(value).map_or(usize::MAX, |json| {
+        json.chars().filter(|c| !c.is_whitespace()).count()
+    })
+}
+
+fn variable_fields(statement: &mut PolicyStatement) -> Vec<&mut String> {
+    statement
+        .resource
+        .iter_mut()
+        .chain(statement.not_resource.iter_mut())
+        .chain(
+            statement
+                .condition
+                .iter_mut()
+                .flat_map(|c| c.conditions.iter_mut())
+                .flat_map(|c| c.values.iter_mut())
+                .filter_map(|v| match v {
+                    ConditionValue::String(value) => Some(value),
+                    _ => None,
+                }),
+        )
+        .filter(|value| value.contains("${"))
+        .collect()
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MergeOptions {
+    pub conflict_policy: ConflictPolicy,
+    pub strategy: MergeStrategy,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MergeOutcome {
+    pub document: PolicyDocument,
+    pub conflicts: Vec<StatementConflict>,
+}
+
+/// Merges documents of version `2012-10-17` into a single `2012-10-17` document.
+///
+/// Documents of version `2008-10-17` are accepted as long as they contain no
+/// policy variables, otherwise they have to be upgraded with
+/// [`PolicyDocument::upgrade`] first. The merged document keeps the `Id` only if
+/// all documents carrying one agree on it. Statements left with a Sid used by
+/// an earlier statement get it with the lowest free numeric suffix.
+pub fn merge_policy_documents(documents: &[PolicyDocument]) -> Result<PolicyDocument, MaipError> {
+    let outcome = merge_policy_documents_with_options(documents, &MergeOptions::default())?;
+    Ok(outcome.document)
+}
+
+/// Like [`merge_policy_documents`], but reports the conflicting Allow and Deny
+/// statements found in the input and resolves them as configured.
+pub fn merge_policy_documents_with_options(
+    documents: &[PolicyDocument],
+    options: &MergeOptions,
+) -> Result<MergeOutcome, MaipError> {
+    for document in documents {
+        match document.effective_version() {
+            VERSION_2012_10_17 => {}
+            VERSION_2008_10_17 if !document.has_policy_variables() => {}
+            VERSION_2008_10_17 => {
+                return Err(MaipError::UpgradeRequired(VERSION_2008_10_17.to_string()))
+            }
+            version => return Err(MaipError::UnsupportedVersion(version.to_string())),
+        }
     }
 
-    let mut new_document = documents.iter().fold(
-        PolicyDocument::new("2012-10-17".to_string(), Vec::new()),
-        |mut acc, document| {
-            acc.statement.extend(document.statement.clone());
+    let mut new_document = documents.iter().enumerate().fold(
+        PolicyDocument::new(VERSION_2012_10_17.to_string(), Vec::new()),
+        |mut acc, (index, document)| {
+            acc.statement
+                .extend(document.statement.iter().map(|statement| PolicyStatement {
+                    origin: BTreeSet::from([index]),
+                    ..statement.clone()
+                }));
             acc
         },
     );
+    new_document.id = merge_ids(documents);
 
+    let conflicts = resolve_conflicts(&mut new_document.statement, options.conflict_policy)?;
+    if options.strategy == MergeStrategy::Optimal {
+        new_document.statement = optimize_statements(new_document.statement);
+    }
     merge_policy_document_statements(&mut new_document);
+    make_sids_unique(&mut new_document.statement);
 
-    Ok(new_document)
+    Ok(MergeOutcome {
+        document: new_document,
+        conflicts,
+    })
+}
+
+fn merge_ids(documents: &[PolicyDocument]) -> Option<String> {
+    let mut ids = documents.iter().filter_map(|d| d.id.as_ref());
+    let id = ids.next()?;
+    if ids.all(|other_id| other_id == id) {
+        return Some(id.clone());
+    }
+    None
 }
 
 pub fn merge_policy_document_statements(document: &mut PolicyDocument) {
+    document.statement = merge_statement_list(&document.statement);
+}
+
+/// Merges every statement into the first statement it can be merged with.
+pub fn merge_statement_list(statements: &[PolicyStatement]) -> Vec<PolicyStatement> {
     let mut merged_statements: Vec<PolicyStatement> = Vec::new();
 
-    for statement in document.statement.iter() {
+    for statement in statements.iter() {
         let mut merged = false;
         for other_statement in merged_statements.iter_mut() {
             if let Some(merged_statement) = merge_statements(statement, other_statement) {
                 *other_statement = merged_statement;
-                other_statement.action.sort_by_key(|a| a.to_lowercase());
+                other_statement.sort();
                 merged = true;
                 break;
             }
@@ -75,29 +256,28 @@ pub fn merge_policy_document_statements(document: &mut PolicyDocument) {
         }
     }
 
-    document.statement = merged_statements;
+    merged_statements
 }
 
-pub fn policy_from_file(file: &str) -> Result<PolicyDocument, String> {
-    let file = File::open(file).map_err(|e| e.to_string())?;
-    let reader = BufReader::new(file);
-    let policy_document: PolicyDocument = from_reader(reader).map_err(|e| e.to_string())?;
+pub fn policy_from_file(file: &str) -> Result<PolicyDocument, MaipError> {
+    let reader = BufReader::new(File::open(file).map_err(|e| MaipError::io(file, e))?);
+    let policy_document: PolicyDocument =
+        from_reader(reader).map_err(|e| MaipError::parse(file, e))?;
     Ok(policy_document)
 }
 
-pub fn policy_to_file(file: &str, policy_document: &PolicyDocument) -> Result<(), String> {
-    let file = File::create(file).map_err(|e| e.to_string())?;
-    let writer = BufWriter::new(file);
-    to_writer_pretty(writer, policy_document).map_err(|e| e.to_string())?;
+pub fn policy_to_file(file: &str, policy_document: &PolicyDocument) -> Result<(), MaipError> {
+    let writer = BufWriter::new(File::create(file).map_err(|e| MaipError::io(file, e))?);
+    to_writer_pretty(writer, policy_document).map_err(|e| MaipError::io(file, e.into()))?;
     Ok(())
 }
 
-pub fn policy_from_arn(arn: &str) -> Result<PolicyDocument, String> {
+pub fn policy_from_arn(arn: &str) -> Result<PolicyDocument, MaipError> {
     let runtime = Builder::new_multi_thread()
         .worker_threads(1)
         .enable_all()
         .build()
-        .unwrap();
+        .map_err(MaipError::Runtime)?;
 
     runtime.block_on(async {
         let config = aws_config::load_from_env().await;
@@ -108,20 +288,30 @@ pub fn policy_from_arn(arn: &str) -> Result<PolicyDocument, String> {
             .policy_arn(arn)
             .send()
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| MaipError::aws_fetch(arn, e))?;
+
+        let version_id = policy
+            .policy()
+            .and_then(|p| p.default_version_id())
+            .ok_or_else(|| MaipError::aws_fetch(arn, "policy has no default version"))?;
 
         let policy_version = client
             .get_policy_version()
             .policy_arn(arn)
-            .version_id(policy.policy().unwrap().default_version_id().unwrap())
+            .version_id(version_id)
             .send()
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| MaipError::aws_fetch(arn, e))?;
 
-        let policy_document = policy_version.policy_version().unwrap().document().unwrap();
-        let policy_document = urlencoding::decode(policy_document).unwrap();
+        let policy_document = policy_version
+            .policy_version()
+            .and_then(|v| v.document())
+            .ok_or_else(|| MaipError::aws_fetch(arn, "policy version has no document"))?;
+        let policy_document =
+            urlencoding::decode(policy_document).map_err(|e| MaipError::aws_fetch(arn, e))?;
 
-        let policy_document: PolicyDocument = serde_json::from_str(&policy_document).unwrap();
+        let policy_document: PolicyDocument =
+            serde_json::from_str(&policy_document).map_err(|e| MaipError::parse(arn, e))?;
         Ok(policy_document)
     })
 }
@@ -1,28 +1,79 @@
+use std::collections::BTreeSet;
+
 use serde::Serialize;
 
 use crate::json_string_or_vec::serialize_string_or_vec;
 
-use super::{merge::Merge, ConditionStatement};
+use super::{
+    condition_statement::{same_conditions, union_condition_values},
+    condition_statement_serde::serialize_conditions,
+    merge::Merge,
+    wildcard::{merge_actions, reduce_actions, reduce_resources, same_actions, same_resources},
+    ConditionStatement, Principal,
+};
 
-#[derive(Serialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct PolicyStatement {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+
     pub effect: String,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub principal: Option<Principal>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_principal: Option<Principal>,
+
     #[serde(
-        deserialize_with = "string_or_seq_string",
+        skip_serializing_if = "Vec::is_empty",
         serialize_with = "serialize_string_or_vec"
     )]
     pub action: Vec<String>,
 
     #[serde(
-        deserialize_with = "string_or_seq_string",
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_string_or_vec"
+    )]
+    pub not_action: Vec<String>,
+
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
         serialize_with = "serialize_string_or_vec"
     )]
     pub resource: Vec<String>,
 
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_string_or_vec"
+    )]
+    pub not_resource: Vec<String>,
+
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_conditions"
+    )]
     pub condition: Vec<ConditionStatement>,
+
+    /// The indices of the merged documents the statement was built from. It is
+    /// not part of the policy and ignored when comparing statements.
+    #[serde(skip)]
+    pub origin: BTreeSet<usize>,
+}
+
+impl PartialEq for PolicyStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.sid == other.sid
+            && self.effect == other.effect
+            && self.principal == other.principal
+            && self.not_principal == other.not_principal
+            && self.action == other.action
+            && self.not_action == other.not_action
+            && self.resource == other.resource
+            && self.not_resource == other.not_resource
+            && self.condition == other.condition
+    }
 }
 
 impl PolicyStatement {
@@ -33,38 +84,67 @@ impl PolicyStatement {
         condition: Vec<ConditionStatement>,
     ) -> Self {
         Self {
+            sid: None,
             effect,
+            principal: None,
+            not_principal: None,
             action,
+            not_action: Vec::new(),
             resource,
+            not_resource: Vec::new(),
             condition,
+            origin: BTreeSet::new(),
         }
     }
 
+    pub fn with_sid(mut self, sid: &str) -> Self {
+        self.sid = Some(sid.to_string());
+        self
+    }
+
+    pub fn with_principal(mut self, principal: Principal) -> Self {
+        self.principal = Some(principal);
+        self
+    }
+
+    pub fn with_not_principal(mut self, not_principal: Principal) -> Self {
+        self.not_principal = Some(not_principal);
+        self
+    }
+
+    /// Turns the statement into a `NotAction` statement matching every action except the given ones.
+    pub fn with_not_action(mut self, not_action: Vec<String>) -> Self {
+        self.action = Vec::new();
+        self.not_action = not_action;
+        self
+    }
+
+    /// Turns the statement into a `NotResource` statement matching every resource except the given ones.
+    pub fn with_not_resource(mut self, not_resource: Vec<String>) -> Self {
+        self.resource = Vec::new();
+        self.not_resource = not_resource;
+        self
+    }
+
     pub fn reduce(&mut self) {
-        let asterisk_actions = self
-            .action
-            .clone()
-            .into_iter()
-            .filter(|a| a.ends_with('*'))
-            .map(|a| a.trim_end_matches('*').to_string())
-            .collect::<Vec<String>>();
-
-        if asterisk_actions.is_empty() {
-            return;
-        }
+        reduce_actions(&mut self.action);
+        reduce_actions(&mut self.not_action);
+        reduce_resources(&mut self.resource);
+        reduce_resources(&mut self.not_resource);
+    }
 
-        self.action.retain(|a| {
-            !asterisk_actions
-                .iter()
-                .any(|asterisk_action| a.starts_with(asterisk_action))
-        });
-
-        self.action.extend(
-            asterisk_actions
-                .into_iter()
-                .map(|a| a + "*")
-                .collect::<Vec<String>>(),
-        );
+    pub fn sort(&mut self) {
+        if let Some(principal) = self.principal.as_mut() {
+            principal.sort();
+        }
+        if let Some(not_principal) = self.not_principal.as_mut() {
+            not_principal.sort();
+        }
+        self.action.sort_by_key(|a| a.to_lowercase());
+        self.not_action.sort_by_key(|a| a.to_lowercase());
+        self.condition
+            .sort_by_key(|condition| condition.operator.to_string());
+        self.condition.iter_mut().for_each(|c| c.sort());
     }
 }
 
@@ -72,62 +152,185 @@ pub fn merge_statements(
     first_statement: &PolicyStatement,
     second_statement: &PolicyStatement,
 ) -> Option<PolicyStatement> {
-    if !first_statement.condition.is_empty() || !second_statement.condition.is_empty() {
-        return None;
+    if !same_conditions(&first_statement.condition, &second_statement.condition) {
+        return merge_condition_values(first_statement, second_statement);
     }
 
+    // Statements with different effects are never merged, an Allow and a Deny
+    // of the same actions and resources is a conflict, see `is_conflict`.
     if first_statement.effect != second_statement.effect {
-        if same_action_and_resource(first_statement, second_statement) {
-            return as_deny_statement(first_statement);
-        }
         return None;
     }
 
     let mut merged_statement = first_statement.clone();
+    merged_statement.sid = merge_sid(&first_statement.sid, &second_statement.sid);
+    merged_statement.origin.extend(&second_statement.origin);
 
-    let first_resource = &first_statement.resource;
-    let second_resource = &second_statement.resource;
+    if !same_principal(first_statement, second_statement) {
+        return merge_principals(merged_statement, second_statement);
+    }
 
-    let first_action = &first_statement.action;
-    let second_action = &second_statement.action;
+    // Only the positive side of a statement is merged by list union. The excluded
+    // lists of NotAction and NotResource statements have to be identical, since
+    // a union would silently narrow the permissions of both statements.
+    if same_action(first_statement, second_statement)
+        && uses_resource(first_statement)
+        && uses_resource(second_statement)
+    {
+        merged_statement
+            .resource
+            .merge(second_statement.resource.clone());
+        reduce_resources(&mut merged_statement.resource);
+        return Some(merged_statement);
+    }
 
-    if first_action == second_action {
-        if let Some(asterisk) = get_asterisk(first_resource, second_resource) {
-            merged_statement.resource = vec![asterisk];
-            return Some(merged_statement);
-        }
-        merged_statement.resource.merge(second_resource.clone());
+    if same_resource(first_statement, second_statement)
+        && uses_action(first_statement)
+        && uses_action(second_statement)
+    {
+        merge_actions(
+            &mut merged_statement.action,
+            second_statement.action.clone(),
+        );
         return Some(merged_statement);
     }
 
-    if first_resource == second_resource {
-        merged_statement.action.merge(second_action.clone());
-        merged_statement.resource.merge(second_resource.clone());
+    if same_action_and_resource(first_statement, second_statement) {
         return Some(merged_statement);
     }
     None
 }
 
+/// Returns true if one statement allows and the other denies exactly the same
+/// actions on the same resources for the same principals and conditions.
+pub fn is_conflict(first_statement: &PolicyStatement, second_statement: &PolicyStatement) -> bool {
+    first_statement.effect != second_statement.effect
+        && same_principal(first_statement, second_statement)
+        && same_action_and_resource(first_statement, second_statement)
+        && same_conditions(&first_statement.condition, &second_statement.condition)
+}
+
+/// Statements that only differ in the values of a single condition key are
+/// merged by a union of those values.
+fn merge_condition_values(
+    first_statement: &PolicyStatement,
+    second_statement: &PolicyStatement,
+) -> Option<PolicyStatement> {
+    if first_statement.effect != second_statement.effect
+        || !same_principal(first_statement, second_statement)
+        || !same_action_and_resource(first_statement, second_statement)
+    {
+        return None;
+    }
+
+    let mut merged_statement = first_statement.clone();
+    merged_statement.sid = merge_sid(&first_statement.sid, &second_statement.sid);
+    merged_statement.origin.extend(&second_statement.origin);
+    merged_statement.condition =
+        union_condition_values(&first_statement.condition, &second_statement.condition)?;
+    Some(merged_statement)
+}
+
+/// Statements granting the same actions on the same resources to different
+/// principals are merged by principal union. NotPrincipal statements are only
+/// merged when their excluded principals are identical.
+fn merge_principals(
+    mut merged_statement: PolicyStatement,
+    second_statement: &PolicyStatement,
+) -> Option<PolicyStatement> {
+    if !same_action_and_resource(&merged_statement, second_statement)
+        || merged_statement.not_principal.is_some()
+        || second_statement.not_principal.is_some()
+    {
+        return None;
+    }
+
+    match (
+        merged_statement.principal.as_mut(),
+        second_statement.principal.clone(),
+    ) {
+        (Some(principal), Some(second_principal)) => {
+            principal.merge(second_principal);
+            Some(merged_statement)
+        }
+        _ => None,
+    }
+}
+
+fn same_principal(first_statement: &PolicyStatement, second_statement: &PolicyStatement) -> bool {
+    first_statement.principal == second_statement.principal
+        && first_statement.not_principal == second_statement.not_principal
+}
+
+fn same_action(first_statement: &PolicyStatement, second_statement: &PolicyStatement) -> bool {
+    same_actions(&first_statement.action, &second_statement.action)
+        && same_actions(&first_statement.not_action, &second_statement.not_action)
+}
+
+fn same_resource(first_statement: &PolicyStatement, second_statement: &PolicyStatement) -> bool {
+    same_resources(&first_statement.resource, &second_statement.resource)
+        && same_resources(
+            &first_statement.not_resource,
+            &second_statement.not_resource,
+        )
+}
+
+fn uses_action(statement: &PolicyStatement) -> bool {
+    statement.not_action.is_empty()
+}
+
+/// A statement without Resource applies to the resource the policy is attached
+/// to, so it is not merged by a union with listed resources.
+fn uses_resource(statement: &PolicyStatement) -> bool {
+    statement.not_resource.is_empty() && !statement.resource.is_empty()
+}
+
 fn same_action_and_resource(
     first_statement: &PolicyStatement,
     second_statement: &PolicyStatement,
 ) -> bool {
-    first_statement.action == second_statement.action
-        && first_statement.resource == second_statement.resource
+    same_action(first_statement, second_statement)
+        && same_resource(first_statement, second_statement)
 }
 
-fn as_deny_statement(statement: &PolicyStatement) -> Option<PolicyStatement> {
-    Some(PolicyStatement::new(
-        "Deny".to_string(),
-        statement.action.clone(),
-        statement.resource.clone(),
-        statement.condition.clone(),
-    ))
+/// A merged statement keeps the Sid both statements have, or the Sid of the
+/// one statement that has one. Different Sids are dropped, since either would
+/// claim permissions for one of the originals that it did not grant, and Sids
+/// may only contain letters and digits, which rules out an unambiguous
+/// combination of both.
+pub fn merge_sid(first_sid: &Option<String>, second_sid: &Option<String>) -> Option<String> {
+    match (first_sid, second_sid) {
+        (Some(first), Some(second)) if first == second => Some(first.clone()),
+        (Some(sid), None) | (None, Some(sid)) => Some(sid.clone()),
+        _ => None,
+    }
 }
 
-fn get_asterisk(first_resources: &[String], second_resources: &[String]) -> Option<String> {
-    if first_resources.contains(&"*".to_string()) || second_resources.contains(&"*".to_string()) {
-        return Some("*".to_string());
+/// Gives every statement whose Sid is already used by an earlier statement the
+/// Sid with the lowest numeric suffix not in use, since Sids have to be unique
+/// within a document.
+pub fn make_sids_unique(statements: &mut [PolicyStatement]) {
+    let mut sids: BTreeSet<String> = statements
+        .iter()
+        .filter_map(|statement| statement.sid.clone())
+        .collect();
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    for statement in statements.iter_mut() {
+        if let Some(sid) = statement.sid.as_ref() {
+            if !seen.insert(sid.clone()) {
+                statement.sid = Some(unused_sid(sid, &mut sids));
+            }
+        }
     }
-    None
+}
+
+/// The Sid with the lowest numeric suffix that is not in use yet, which is
+/// then marked as used.
+pub fn unused_sid(sid: &str, sids: &mut BTreeSet<String>) -> String {
+    let unused = (1..)
+        .map(|number| format!("{}{}", sid, number))
+        .find(|candidate| !sids.contains(candidate))
+        .unwrap_or_default();
+    sids.insert(unused.clone());
+    unused
 }
@@ -1,9 +1,11 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use serde::{de::Error, Deserialize};
 use serde_json::Value;
 
-use super::{Condition, ConditionStatement, PolicyStatement};
+use crate::json_string_or_vec::StringOrVec;
+
+use super::{Condition, ConditionStatement, ConditionValue, PolicyStatement, Principal};
 
 impl<'de> Deserialize<'de> for PolicyStatement {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -27,46 +29,62 @@ impl<'de> serde::de::Visitor<'de> for PolicyStatementVisitor {
     where
         A: serde::de::MapAccess<'de>,
     {
+        let mut sid = None;
         let mut effect = None;
+        let mut principal = None;
+        let mut not_principal = None;
         let mut action = None;
+        let mut not_action = None;
         let mut resource = None;
+        let mut not_resource = None;
         let mut condition = None;
 
         while let Some(key) = map.next_key::<String>()? {
             match key.as_str() {
+                "Sid" => {
+                    sid = Some(map.next_value::<String>()?);
+                }
                 "Effect" => {
                     effect = Some(map.next_value::<String>()?);
                 }
+                "Principal" => {
+                    principal = Some(map.next_value::<Principal>()?);
+                }
+                "NotPrincipal" => {
+                    not_principal = Some(map.next_value::<Principal>()?);
+                }
                 "Action" => {
-                    let value: Value = map.next_value().unwrap();
-                    action = Some(get_value_as_vec(&value));
+                    action = Some(map.next_value::<StringOrVec>()?.0);
+                }
+                "NotAction" => {
+                    not_action = Some(map.next_value::<StringOrVec>()?.0);
                 }
                 "Resource" => {
-                    let value: Value = map.next_value().unwrap();
-                    resource = Some(get_value_as_vec(&value));
+                    resource = Some(map.next_value::<StringOrVec>()?.0);
+                }
+                "NotResource" => {
+                    not_resource = Some(map.next_value::<StringOrVec>()?.0);
                 }
                 "Condition" => {
+                    // Operators and condition keys are collected in sorted maps so that the
+                    // order of the parsed conditions does not depend on hashing.
                     let conditions = map
-                        .next_value::<HashMap<String, HashMap<String, Value>>>()?
+                        .next_value::<BTreeMap<String, BTreeMap<String, Value>>>()?
                         .iter()
-                        .map(|(key, condition)| {
-                            (
-                                key,
-                                condition
-                                    .iter()
-                                    .map(|(condition_key, condition_value)| {
-                                        Condition::new(
-                                            condition_key.to_owned(),
-                                            get_value_as_vec(condition_value),
-                                        )
-                                    })
-                                    .collect::<Vec<Condition>>(),
-                            )
-                        })
                         .map(|(operator, condition)| {
-                            ConditionStatement::new(operator.to_owned(), condition)
+                            let conditions = condition
+                                .iter()
+                                .map(|(condition_key, condition_value)| {
+                                    Ok(Condition::new(
+                                        condition_key.to_owned(),
+                                        get_condition_values(condition_value)?,
+                                    ))
+                                })
+                                .collect::<Result<Vec<Condition>, A::Error>>()?;
+                            let operator = operator.parse().map_err(Error::custom)?;
+                            Ok(ConditionStatement::new(operator, conditions))
                         })
-                        .collect::<Vec<ConditionStatement>>();
+                        .collect::<Result<Vec<ConditionStatement>, A::Error>>()?;
                     condition = Some(conditions);
                 }
                 _ => {
@@ -76,26 +94,223 @@ impl<'de> serde::de::Visitor<'de> for PolicyStatementVisitor {
         }
 
         let effect = effect.ok_or_else(|| Error::missing_field("Effect"))?;
-        let action = action.ok_or_else(|| Error::missing_field("Action"))?;
-        let resource = resource.ok_or_else(|| Error::missing_field("Resource"))?;
+        if principal.is_some() && not_principal.is_some() {
+            return Err(Error::custom(
+                "a statement cannot contain both Principal and NotPrincipal",
+            ));
+        }
+        // Resource-based policies such as role trust policies have no Resource
+        // element, the resource is the one the policy is attached to.
+        let resource_based = principal.is_some() || not_principal.is_some();
+
+        let (action, not_action) = exactly_one_of(action, not_action, "Action", "NotAction", true)?;
+        let (resource, not_resource) = exactly_one_of(
+            resource,
+            not_resource,
+            "Resource",
+            "NotResource",
+            !resource_based,
+        )?;
         let condition = condition.unwrap_or_default();
 
         Ok(PolicyStatement {
+            sid,
             effect,
+            principal,
+            not_principal,
             action,
+            not_action,
             resource,
+            not_resource,
             condition,
+            origin: Default::default(),
         })
     }
 }
 
-fn get_value_as_vec(value: &Value) -> Vec<String> {
+fn exactly_one_of<E: Error>(
+    value: Option<Vec<String>>,
+    not_value: Option<Vec<String>>,
+    field: &'static str,
+    not_field: &'static str,
+    required: bool,
+) -> Result<(Vec<String>, Vec<String>), E> {
+    match (value, not_value) {
+        (Some(_), Some(_)) => Err(Error::custom(format!(
+            "a statement cannot contain both {} and {}",
+            field, not_field
+        ))),
+        (Some(value), None) => Ok((value, Vec::new())),
+        (None, Some(not_value)) => Ok((Vec::new(), not_value)),
+        (None, None) if required => Err(Error::missing_field(field)),
+        (None, None) => Ok((Vec::new(), Vec::new())),
+    }
+}
+
+fn get_condition_values<E: Error>(value: &Value) -> Result<Vec<ConditionValue>, E> {
+    match value {
+        Value::Array(array) => array.iter().map(get_condition_value).collect(),
+        _ => Ok(vec![get_condition_value(value)?]),
+    }
+}
+
+fn get_condition_value<E: Error>(value: &Value) -> Result<ConditionValue, E> {
     match value {
-        Value::Array(array) => array
+        Value::String(value) => Ok(ConditionValue::String(value.to_owned())),
+        Value::Bool(value) => Ok(ConditionValue::Bool(*value)),
+        Value::Number(value) => Ok(ConditionValue::Number(value.clone())),
+        Value::Null => Ok(ConditionValue::Null),
+        _ => Err(Error::custom(format!(
+            "invalid condition value {}, expected a string, number, boolean or null",
+            value
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_statement_with_sid() {
+        let json =
+            r#"{"Sid":"AllowDescribe","Effect":"Allow","Action":"ec2:Describe*","Resource":"*"}"#;
+
+        let statement: PolicyStatement = serde_json::from_str(json).unwrap();
+
+        assert_eq!(statement.sid, Some("AllowDescribe".to_string()));
+        assert_eq!(serde_json::to_string(&statement).unwrap(), json);
+    }
+
+    #[test]
+    fn test_deserialize_not_action_and_not_resource() {
+        let json = r#"{"Effect":"Deny","NotAction":["iam:*","sts:*"],"NotResource":"arn:aws:s3:::logs/*"}"#;
+
+        let statement: PolicyStatement = serde_json::from_str(json).unwrap();
+
+        assert!(statement.action.is_empty());
+        assert_eq!(statement.not_action, vec!["iam:*", "sts:*"]);
+        assert!(statement.resource.is_empty());
+        assert_eq!(statement.not_resource, vec!["arn:aws:s3:::logs/*"]);
+        assert_eq!(serde_json::to_string(&statement).unwrap(), json);
+    }
+
+    #[test]
+    fn test_deserialize_action_and_not_action() {
+        let json = r#"{"Effect":"Deny","Action":"s3:*","NotAction":"iam:*","Resource":"*"}"#;
+
+        let result = serde_json::from_str::<PolicyStatement>(json);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_trust_policy_statement() {
+        let json = r#"{"Effect":"Allow","Principal":{"Service":"ec2.amazonaws.com"},"Action":"sts:AssumeRole"}"#;
+
+        let statement: PolicyStatement = serde_json::from_str(json).unwrap();
+
+        assert!(statement.principal.is_some());
+        assert!(statement.resource.is_empty());
+        assert_eq!(serde_json::to_string(&statement).unwrap(), json);
+    }
+
+    #[test]
+    fn test_deserialize_missing_resource_without_principal() {
+        let json = r#"{"Effect":"Allow","Action":"sts:AssumeRole"}"#;
+
+        let result = serde_json::from_str::<PolicyStatement>(json);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_invalid_action_type() {
+        let json = r#"{"Effect":"Allow","Action":42,"Resource":"*"}"#;
+
+        let result = serde_json::from_str::<PolicyStatement>(json);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_unknown_condition_operator() {
+        let json = r#"{"Effect":"Allow","Action":"s3:*","Resource":"*","Condition":{"StringEqual":{"aws:PrincipalTag/team":"ops"}}}"#;
+
+        let result = serde_json::from_str::<PolicyStatement>(json);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_conditions_in_canonical_order() {
+        let json = r#"{"Effect":"Allow","Action":"s3:*","Resource":"*","Condition":{"StringLike":{"s3:prefix":"home/*","aws:SourceVpc":"vpc-1"},"Bool":{"aws:SecureTransport":"true"}}}"#;
+
+        let statement: PolicyStatement = serde_json::from_str(json).unwrap();
+
+        let operators = statement
+            .condition
+            .iter()
+            .map(|c| c.operator.to_string())
+            .collect::<Vec<String>>();
+        let keys = statement.condition[1]
+            .conditions
             .iter()
-            .map(|v| v.as_str().unwrap().to_owned())
-            .collect::<Vec<String>>(),
-        Value::String(string) => vec![string.to_owned()],
-        _ => panic!("Invalid value type"),
+            .map(|c| c.key.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(operators, vec!["Bool", "StringLike"]);
+        assert_eq!(keys, vec!["aws:SourceVpc", "s3:prefix"]);
+    }
+
+    #[test]
+    fn test_condition_round_trip() {
+        let json = r#"{"Effect":"Allow","Action":"s3:GetObject","Resource":"*","Condition":{"Bool":{"aws:SecureTransport":true},"ForAnyValue:StringLike":{"aws:TagKeys":["team","env"]},"StringEquals":{"aws:PrincipalTag/team":"ops","aws:RequestedRegion":["eu-west-1","eu-central-1"]}}}"#;
+
+        let statement: PolicyStatement = serde_json::from_str(json).unwrap();
+
+        assert_eq!(serde_json::to_string(&statement).unwrap(), json);
+    }
+
+    #[test]
+    fn test_serialize_repeated_condition_key_fails() {
+        let statement = PolicyStatement::new(
+            "Allow".to_string(),
+            vec!["ec2:*".to_string()],
+            vec!["*".to_string()],
+            vec![
+                ConditionStatement::new(
+                    "StringEquals".parse().unwrap(),
+                    vec![Condition::new("ec2:Region".to_string(), vec!["us-east-1"])],
+                ),
+                ConditionStatement::new(
+                    "StringEquals".parse().unwrap(),
+                    vec![Condition::new("ec2:Region".to_string(), vec!["us-east-2"])],
+                ),
+            ],
+        );
+
+        let error = serde_json::to_string(&statement).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Condition key ec2:Region appears twice below the same operator"
+        );
+    }
+
+    #[test]
+    fn test_serialize_statement_without_sid() {
+        let statement = PolicyStatement::new(
+            "Allow".to_string(),
+            vec!["ec2:Describe*".to_string()],
+            vec!["*".to_string()],
+            Vec::new(),
+        );
+
+        let json = serde_json::to_string(&statement).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"Effect":"Allow","Action":"ec2:Describe*","Resource":"*"}"#
+        );
     }
 }
@@ -0,0 +1,75 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use super::merge::Merge;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Principal {
+    /// The `"*"` principal, matching everyone including anonymous users.
+    Any,
+    Specific(BTreeMap<PrincipalType, Vec<String>>),
+}
+
+impl Principal {
+    pub fn new(principal_type: PrincipalType, values: Vec<String>) -> Self {
+        Self::Specific(BTreeMap::from([(principal_type, values)]))
+    }
+
+    pub fn sort(&mut self) {
+        if let Principal::Specific(principals) = self {
+            principals.values_mut().for_each(|values| values.sort());
+        }
+    }
+}
+
+impl Merge for Principal {
+    fn merge(&mut self, other: Self) {
+        match (self, other) {
+            (Principal::Any, _) => {}
+            (principal, Principal::Any) => *principal = Principal::Any,
+            (Principal::Specific(principals), Principal::Specific(other_principals)) => {
+                for (principal_type, values) in other_principals {
+                    principals.entry(principal_type).or_default().merge(values);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum PrincipalType {
+    Aws,
+    Service,
+    Federated,
+    CanonicalUser,
+}
+
+impl PrincipalType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrincipalType::Aws => "AWS",
+            PrincipalType::Service => "Service",
+            PrincipalType::Federated => "Federated",
+            PrincipalType::CanonicalUser => "CanonicalUser",
+        }
+    }
+}
+
+impl fmt::Display for PrincipalType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PrincipalType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "AWS" => Ok(PrincipalType::Aws),
+            "Service" => Ok(PrincipalType::Service),
+            "Federated" => Ok(PrincipalType::Federated),
+            "CanonicalUser" => Ok(PrincipalType::CanonicalUser),
+            _ => Err(format!("Unknown principal type: {}", s)),
+        }
+    }
+}
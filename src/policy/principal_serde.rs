@@ -0,0 +1,98 @@
+use serde::{de::Error, ser::SerializeMap, Deserialize, Serialize};
+
+use crate::json_string_or_vec::StringOrVec;
+
+use super::{Principal, PrincipalType};
+
+impl Serialize for Principal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Principal::Any => serializer.serialize_str("*"),
+            Principal::Specific(principals) => {
+                let mut map = serializer.serialize_map(Some(principals.len()))?;
+                for (principal_type, values) in principals {
+                    map.serialize_entry(principal_type.as_str(), &StringOrVec(values.clone()))?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Principal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(PrincipalVisitor)
+    }
+}
+
+struct PrincipalVisitor;
+
+impl<'de> serde::de::Visitor<'de> for PrincipalVisitor {
+    type Value = Principal;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("\"*\" or a map of principal types to principals")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match value {
+            "*" => Ok(Principal::Any),
+            _ => Err(Error::invalid_value(
+                serde::de::Unexpected::Str(value),
+                &self,
+            )),
+        }
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut principals = std::collections::BTreeMap::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            let principal_type = key.parse::<PrincipalType>().map_err(Error::custom)?;
+            let StringOrVec(values) = map.next_value()?;
+            principals.insert(principal_type, values);
+        }
+
+        Ok(Principal::Specific(principals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_principal_round_trip() {
+        let json = r#"{"AWS":["arn:aws:iam::111122223333:root","arn:aws:iam::444455556666:root"],"Service":"ec2.amazonaws.com"}"#;
+
+        let principal: Principal = serde_json::from_str(json).unwrap();
+
+        assert_eq!(serde_json::to_string(&principal).unwrap(), json);
+    }
+
+    #[test]
+    fn test_deserialize_any_principal() {
+        let principal: Principal = serde_json::from_str(r#""*""#).unwrap();
+
+        assert_eq!(principal, Principal::Any);
+    }
+
+    #[test]
+    fn test_deserialize_unknown_principal_type() {
+        let result = serde_json::from_str::<Principal>(r#"{"User":"alice"}"#);
+
+        assert!(result.is_err());
+    }
+}
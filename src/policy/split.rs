@@ -0,0 +1,242 @@
+use std::collections::BTreeSet;
+
+use crate::error::MaipError;
+
+use super::{
+    policy_document::json_size, policy_statement::unused_sid, PolicyDocument, PolicyStatement,
+};
+
+/// Splits a document into documents that each fit into `limit`.
+///
+/// Statements are packed first fit decreasing: in decreasing order of their
+/// size, each into the first document with room for it. A statement that does
+/// not fit into an empty document is split into two halves of its Action or
+/// Resource list, whichever is longer. The halves keep the Sid with the lowest
+/// numeric suffix not used by another statement, since Sids have to be unique
+/// within a document. If there is more than one document, each gets the Id of
+/// the original with its number as suffix.
+pub fn split_policy_document(
+    document: &PolicyDocument,
+    limit: usize,
+) -> Result<Vec<PolicyDocument>, MaipError> {
+    let empty_document = PolicyDocument {
+        statement: Vec::new(),
+        ..document.clone()
+    };
+
+    let mut sids: BTreeSet<String> = document
+        .statement
+        .iter()
+        .filter_map(|statement| statement.sid.clone())
+        .collect();
+    let mut statements = Vec::new();
+    for statement in document.statement.iter() {
+        statements.extend(split_statement(
+            &empty_document,
+            statement.clone(),
+            statement.sid.as_deref(),
+            &mut sids,
+            limit,
+        )?);
+    }
+    statements.sort_by_cached_key(|statement| std::cmp::Reverse(json_size(statement)));
+
+    let mut documents: Vec<PolicyDocument> = Vec::new();
+    for statement in statements {
+        match documents
+            .iter_mut()
+            .find(|document| fits(document, &statement, limit))
+        {
+            Some(document) => document.statement.push(statement),
+            None => documents.push(PolicyDocument {
+                statement: vec![statement],
+                ..empty_document.clone()
+            }),
+        }
+    }
+
+    if documents.is_empty() {
+        documents.push(empty_document);
+    }
+    if let (Some(id), true) = (document.id.as_ref(), documents.len() > 1) {
+        for (index, document) in documents.iter_mut().enumerate() {
+            document.id = Some(format!("{}-{}", id, index + 1));
+        }
+    }
+    Ok(documents)
+}
+
+/// Splits the statement until every part fits. `sid` is the Sid of the
+/// original statement and `sids` the Sids already in use.
+fn split_statement(
+    empty_document: &PolicyDocument,
+    statement: PolicyStatement,
+    sid: Option<&str>,
+    sids: &mut BTreeSet<String>,
+    limit: usize,
+) -> Result<Vec<PolicyStatement>, MaipError> {
+    if fits(empty_document, &statement, limit) {
+        return Ok(vec![statement]);
+    }
+
+    let (mut first, mut second) = halve(&statement).ok_or_else(|| MaipError::SizeLimit {
+        subject: match statement.sid.as_ref() {
+            Some(sid) => format!("Statement {}", sid),
+            None => "Statement".to_string(),
+        },
+        size: size_with(empty_document, &statement),
+        limit,
+    })?;
+
+    if let (Some(sid), Some(statement_sid)) = (sid, statement.sid.as_ref()) {
+        sids.remove(statement_sid);
+        first.sid = Some(unused_sid(sid, sids));
+        second.sid = Some(unused_sid(sid, sids));
+    }
+
+    let mut statements = split_statement(empty_document, first, sid, sids, limit)?;
+    statements.extend(split_statement(empty_document, second, sid, sids, limit)?);
+    Ok(statements)
+}
+
+/// Splits the longer of the Action and Resource lists into two statements.
+/// NotAction and NotResource lists are never split, since every half would
+/// match more than the original statement.
+fn halve(statement: &PolicyStatement) -> Option<(PolicyStatement, PolicyStatement)> {
+    let mut first = statement.clone();
+    let mut second = statement.clone();
+
+    if statement.action.len() >= statement.resource.len() && statement.action.len() > 1 {
+        second.action = first.action.split_off(first.action.len() / 2);
+    } else if statement.resource.len() > 1 {
+        second.resource = first.resource.split_off(first.resource.len() / 2);
+    } else {
+        return None;
+    }
+    Some((first, second))
+}
+
+fn fits(document: &PolicyDocument, statement: &PolicyStatement, limit: usize) -> bool {
+    size_with(document, statement) <= limit
+}
+
+fn size_with(document: &PolicyDocument, statement: &PolicyStatement) -> usize {
+    let mut document = document.clone();
+    document.statement.push(statement.clone());
+    document.size()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{allow, document};
+
+    #[test]
+    fn test_split_keeps_document_under_limit() {
+        let original = document(vec![allow(&["s3:GetObject"], &["*"])]);
+
+        let documents = split_policy_document(&original, 1000).unwrap();
+
+        assert_eq!(documents, vec![original]);
+    }
+
+    #[test]
+    fn test_split_packs_statements_first_fit_decreasing() {
+        let original = document(vec![
+            allow(&["s3:GetObject"], &["arn:aws:s3:::first/*"]),
+            allow(&["s3:PutObject"], &["arn:aws:s3:::second/*"]),
+            allow(&["s3:DeleteObject"], &["arn:aws:s3:::third/*"]),
+        ]);
+        let limit = document(original.statement[..2].to_vec()).size();
+
+        let documents = split_policy_document(&original, limit).unwrap();
+
+        assert_eq!(documents.len(), 2);
+        assert!(documents.iter().all(|d| d.size() <= limit));
+        assert_eq!(
+            documents.iter().map(|d| d.statement.len()).sum::<usize>(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_split_oversized_statement_by_action() {
+        let original = document(vec![allow(
+            &[
+                "s3:GetObject",
+                "s3:PutObject",
+                "s3:DeleteObject",
+                "s3:ListBucket",
+            ],
+            &["*"],
+        )
+        .with_sid("Objects")]);
+        let limit = document(vec![
+            allow(&["s3:DeleteObject", "s3:ListBucket"], &["*"]).with_sid("Objects2")
+        ])
+        .size();
+
+        let documents = split_policy_document(&original, limit).unwrap();
+
+        assert_eq!(
+            documents
+                .iter()
+                .flat_map(|d| d.statement.iter())
+                .map(|s| s.sid.clone().unwrap())
+                .collect::<Vec<String>>(),
+            vec!["Objects2", "Objects1"]
+        );
+        assert!(documents.iter().all(|d| d.size() <= limit));
+    }
+
+    #[test]
+    fn test_split_gives_halves_unused_sids() {
+        let original = document(vec![
+            allow(&["s3:GetObject", "s3:PutObject", "s3:DeleteObject"], &["*"]).with_sid("Objects"),
+            allow(&["s3:ListBucket"], &["*"]).with_sid("Objects1"),
+        ]);
+        let limit = document(vec![
+            allow(&["s3:PutObject", "s3:DeleteObject"], &["*"]).with_sid("Objects2")
+        ])
+        .size();
+
+        let documents = split_policy_document(&original, limit).unwrap();
+
+        let mut sids = documents
+            .iter()
+            .flat_map(|d| d.statement.iter())
+            .map(|s| s.sid.clone().unwrap())
+            .collect::<Vec<String>>();
+        sids.sort();
+        assert_eq!(sids, vec!["Objects1", "Objects2", "Objects3"]);
+    }
+
+    #[test]
+    fn test_split_numbers_ids() {
+        let original = document(vec![
+            allow(&["s3:GetObject"], &["arn:aws:s3:::first/*"]),
+            allow(&["s3:PutObject"], &["arn:aws:s3:::second/*"]),
+        ])
+        .with_id("Storage");
+        let limit = document(vec![original.statement[0].clone()]).size() + 20;
+
+        let documents = split_policy_document(&original, limit).unwrap();
+
+        assert_eq!(
+            documents
+                .iter()
+                .map(|d| d.id.clone().unwrap())
+                .collect::<Vec<String>>(),
+            vec!["Storage-1", "Storage-2"]
+        );
+    }
+
+    #[test]
+    fn test_split_fails_for_statement_that_cannot_be_split() {
+        let original = document(vec![allow(&["s3:GetObject"], &["*"])]);
+
+        let result = split_policy_document(&original, 10);
+
+        assert!(matches!(result, Err(MaipError::SizeLimit { .. })));
+    }
+}
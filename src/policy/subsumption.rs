@@ -0,0 +1,85 @@
+use super::{
+    condition_statement::same_conditions,
+    wildcard::{any_action_covers, any_resource_covers},
+    PolicyStatement,
+};
+
+/// Removes actions, resources and whole statements that are already granted by
+/// another Allow statement.
+///
+/// An Allow statement covers another one if it has the same principals and
+/// either no conditions or the same conditions. Actions of the covered
+/// statement are dropped when the covering statement grants them on all of its
+/// resources, resources are dropped when the covering statement grants all of
+/// its actions on them. Statements left without actions or resources are
+/// removed. Deny statements are never changed.
+pub fn remove_subsumed_statements(statements: &mut Vec<PolicyStatement>) {
+    let mut removed = vec![false; statements.len()];
+
+    for target_index in 0..statements.len() {
+        for cover_index in 0..statements.len() {
+            if target_index == cover_index || removed[cover_index] || removed[target_index] {
+                continue;
+            }
+
+            let cover = &statements[cover_index];
+            let target = &statements[target_index];
+            if !can_cover(cover, target) {
+                continue;
+            }
+
+            let cover_actions = cover.action.clone();
+            let cover_resources = cover.resource.clone();
+            let target = &mut statements[target_index];
+            let target_size = (target.action.len(), target.resource.len());
+
+            if all_resources_covered(&cover_resources, &target.resource) {
+                target
+                    .action
+                    .retain(|action| !any_action_covers(&cover_actions, action));
+            } else if target
+                .action
+                .iter()
+                .all(|action| any_action_covers(&cover_actions, action))
+            {
+                target
+                    .resource
+                    .retain(|resource| !any_resource_covers(&cover_resources, resource));
+            }
+
+            removed[target_index] = target.action.is_empty()
+                || (target.resource.is_empty() && !cover_resources.is_empty());
+
+            // The cover now grants what was removed from the target.
+            if target_size != (target.action.len(), target.resource.len()) {
+                let origin = target.origin.clone();
+                statements[cover_index].origin.extend(origin);
+            }
+        }
+    }
+
+    let mut removed = removed.into_iter();
+    statements.retain(|_| !removed.next().unwrap_or(false));
+}
+
+fn can_cover(cover: &PolicyStatement, target: &PolicyStatement) -> bool {
+    cover.effect == "Allow"
+        && target.effect == "Allow"
+        && uses_action_and_resource(cover)
+        && uses_action_and_resource(target)
+        && cover.principal == target.principal
+        && cover.not_principal.is_none()
+        && target.not_principal.is_none()
+        && cover.resource.is_empty() == target.resource.is_empty()
+        && (cover.condition.is_empty() || same_conditions(&cover.condition, &target.condition))
+}
+
+fn uses_action_and_resource(statement: &PolicyStatement) -> bool {
+    statement.not_action.is_empty() && statement.not_resource.is_empty()
+}
+
+fn all_resources_covered(cover_resources: &[String], resources: &[String]) -> bool {
+    resources
+        .iter()
+        .all(|resource| any_resource_covers(cover_resources, resource))
+}
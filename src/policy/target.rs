@@ -0,0 +1,114 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::MaipError;
+
+use super::PolicyDocument;
+
+/// Where a policy is attached, which determines its size limit.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum PolicyTarget {
+    #[default]
+    Managed,
+    UserInline,
+    RoleInline,
+    GroupInline,
+    /// A service control policy of AWS Organizations.
+    Scp,
+}
+
+impl PolicyTarget {
+    /// The maximum size of a policy in characters, not counting whitespace.
+    pub fn size_limit(&self) -> usize {
+        match self {
+            PolicyTarget::Managed => 6144,
+            PolicyTarget::UserInline => 2048,
+            PolicyTarget::RoleInline => 10240,
+            PolicyTarget::GroupInline => 5120,
+            PolicyTarget::Scp => 5120,
+        }
+    }
+
+    /// Fails if the document is larger than the size limit of the target.
+    pub fn check_size(&self, document: &PolicyDocument) -> Result<(), MaipError> {
+        let size = document.size();
+        if size > self.size_limit() {
+            return Err(MaipError::SizeLimit {
+                subject: format!("Policy for target {}", self),
+                size,
+                limit: self.size_limit(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PolicyTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyTarget::Managed => f.write_str("managed"),
+            PolicyTarget::UserInline => f.write_str("user-inline"),
+            PolicyTarget::RoleInline => f.write_str("role-inline"),
+            PolicyTarget::GroupInline => f.write_str("group-inline"),
+            PolicyTarget::Scp => f.write_str("scp"),
+        }
+    }
+}
+
+impl FromStr for PolicyTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "managed" => Ok(PolicyTarget::Managed),
+            "user-inline" => Ok(PolicyTarget::UserInline),
+            "role-inline" => Ok(PolicyTarget::RoleInline),
+            "group-inline" => Ok(PolicyTarget::GroupInline),
+            "scp" => Ok(PolicyTarget::Scp),
+            _ => Err(format!(
+                "Unknown target: {}, expected managed, role-inline, user-inline, group-inline or scp",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::PolicyStatement;
+
+    #[test]
+    fn test_target_from_str() {
+        assert_eq!("managed".parse(), Ok(PolicyTarget::Managed));
+        assert_eq!("role-inline".parse(), Ok(PolicyTarget::RoleInline));
+        assert_eq!("scp".parse(), Ok(PolicyTarget::Scp));
+        assert!("inline".parse::<PolicyTarget>().is_err());
+    }
+
+    #[test]
+    fn test_check_size_reports_overshoot() {
+        let document = PolicyDocument::new(
+            "2012-10-17".to_string(),
+            vec![PolicyStatement::new(
+                "Allow".to_string(),
+                vec!["s3:GetObject".to_string()],
+                (0..100)
+                    .map(|i| format!("arn:aws:s3:::bucket-{}/*", i))
+                    .collect(),
+                Vec::new(),
+            )],
+        );
+
+        assert!(PolicyTarget::Managed.check_size(&document).is_ok());
+
+        let error = PolicyTarget::UserInline.check_size(&document).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Policy for target user-inline has {} characters, {} more than the limit of 2048",
+                document.size(),
+                document.size() - 2048
+            )
+        );
+    }
+}
@@ -0,0 +1,681 @@
+use std::fmt;
+
+use super::{
+    evaluation::{condition_holds, request_values, RequestValue},
+    wildcard::{action_covers, instances, resource_covers},
+    ConditionOperator, ConditionValue, PolicyDocument, PolicyStatement, Principal, PrincipalType,
+};
+
+/// The result of evaluating a request against a set of policies.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Decision {
+    Allow,
+    /// Denied by a Deny statement.
+    Deny,
+    /// Not allowed by any statement.
+    ImplicitDeny,
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Decision::Allow => f.write_str("allow"),
+            Decision::Deny => f.write_str("deny"),
+            Decision::ImplicitDeny => f.write_str("implicit deny"),
+        }
+    }
+}
+
+/// A request that the merged policies decide differently than the input.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Counterexample {
+    pub action: String,
+    pub resource: String,
+    /// The principal of the request, if any statement names principals.
+    pub principal: Option<String>,
+    /// The condition values of the request, if any statement has conditions.
+    pub conditions: Vec<String>,
+    /// The decision of the input policies.
+    pub expected: Decision,
+    /// The decision of the merged policies.
+    pub actual: Decision,
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on {}", self.action, self.resource)?;
+        if let Some(principal) = self.principal.as_ref() {
+            write!(f, " by {}", principal)?;
+        }
+        if !self.conditions.is_empty() {
+            write!(f, " with {}", self.conditions.join(", "))?;
+        }
+        write!(f, ": expected {}, got {}", self.expected, self.actual)
+    }
+}
+
+/// The number of requests above which [`verify_equivalence`] gives up.
+pub const MAX_REQUESTS: usize = 100_000_000;
+
+/// The reason why the merged policies could not be verified.
+#[derive(Debug, PartialEq, Clone)]
+pub enum VerifyError {
+    /// A request is decided differently by the merged policies.
+    NotEquivalent(Counterexample),
+    /// There are more requests to check than [`MAX_REQUESTS`].
+    TooLarge { requests: usize },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::NotEquivalent(counterexample) => write!(
+                f,
+                "The merged policies are not equivalent to the input: {}",
+                counterexample
+            ),
+            VerifyError::TooLarge { requests } => write!(
+                f,
+                "Too many requests to verify the merge: {}, at most {}",
+                requests, MAX_REQUESTS
+            ),
+        }
+    }
+}
+
+/// Checks that the merged documents decide a finite set of requests like the
+/// input documents, taking the statements of all documents of a side together.
+///
+/// The actions of the requests are the given ones, usually those of the
+/// service catalog, every action of both sides, an instance of every wildcard
+/// of both sides and an unknown action of every service named by them.
+/// Wildcards and unknown actions of the given complete services are left out,
+/// since the known actions are all they can match. The resources are the
+/// resources of both sides and instances of their wildcards. Principals come
+/// from both sides, and every condition key takes the values from
+/// [`request_values`], which the operators are evaluated on.
+///
+/// Equivalence on these requests does not prove equivalence on all of them,
+/// but finds the differences a merge can introduce in practice.
+pub fn verify_equivalence(
+    inputs: &[PolicyDocument],
+    merged: &[PolicyDocument],
+    known_actions: &[String],
+    complete_services: &[String],
+) -> Result<(), VerifyError> {
+    let expected = statements(inputs);
+    let actual = statements(merged);
+    let universe = Universe::new(&expected, &actual, known_actions, complete_services);
+
+    let requests = universe.requests();
+    if requests > MAX_REQUESTS {
+        return Err(VerifyError::TooLarge { requests });
+    }
+
+    let expected = Side::new(&expected, &universe);
+    let actual = Side::new(&actual, &universe);
+
+    let mut context = vec![0; universe.keys.len()];
+    loop {
+        for principal in 0..universe.principals.len() {
+            let expected_active = expected.active(&universe, principal, &context);
+            let actual_active = actual.active(&universe, principal, &context);
+
+            for action in 0..universe.actions.len() {
+                for resource in 0..universe.resources.len() {
+                    let expected = expected.decide(&expected_active, action, resource);
+                    let actual = actual.decide(&actual_active, action, resource);
+                    if expected != actual {
+                        return Err(VerifyError::NotEquivalent(Counterexample {
+                            action: universe.actions[action].clone(),
+                            resource: universe.resources[resource].clone(),
+                            principal: universe.describe_principal(principal),
+                            conditions: universe.describe_context(&context),
+                            expected,
+                            actual,
+                        }));
+                    }
+                }
+            }
+        }
+        if !universe.next_context(&mut context) {
+            return Ok(());
+        }
+    }
+}
+
+fn statements(documents: &[PolicyDocument]) -> Vec<&PolicyStatement> {
+    documents
+        .iter()
+        .flat_map(|document| document.statement.iter())
+        .collect()
+}
+
+/// A principal of a request, `None` for one not named by any statement.
+type PrincipalAtom = Option<(PrincipalType, String)>;
+
+/// A condition key with the operators and values of every condition on it.
+type KeyConditions<'a> = (String, Vec<(ConditionOperator, &'a [ConditionValue])>);
+
+/// A condition key with the values a request may have for it.
+struct ConditionKey {
+    name: String,
+    values: Vec<RequestValue>,
+}
+
+struct Universe {
+    actions: Vec<String>,
+    resources: Vec<String>,
+    principals: Vec<PrincipalAtom>,
+    keys: Vec<ConditionKey>,
+}
+
+impl Universe {
+    fn new(
+        inputs: &[&PolicyStatement],
+        merged: &[&PolicyStatement],
+        known_actions: &[String],
+        complete_services: &[String],
+    ) -> Self {
+        let statements: Vec<&PolicyStatement> =
+            inputs.iter().chain(merged.iter()).copied().collect();
+        let complete = |prefix: &str| {
+            complete_services
+                .iter()
+                .any(|service| service.eq_ignore_ascii_case(prefix))
+        };
+
+        let mut actions: Vec<String> = known_actions.to_vec();
+        let mut unknown: Vec<String> = Vec::new();
+        for pattern in statements
+            .iter()
+            .flat_map(|s| s.action.iter().chain(s.not_action.iter()))
+        {
+            let prefix = pattern.split_once(':').map(|(prefix, _)| prefix);
+            if let Some(prefix) = prefix.filter(|p| !p.contains(['*', '?']) && !complete(p)) {
+                unknown.push(format!("{}:maipprobe", prefix));
+            }
+            if !pattern.contains(['*', '?']) || !prefix.is_some_and(complete) {
+                actions.extend(instances(pattern));
+            }
+        }
+        actions.extend(unknown);
+        actions.push("maip:Probe".to_string());
+        let mut distinct: Vec<String> = Vec::new();
+        for action in actions {
+            if !distinct.iter().any(|a| a.eq_ignore_ascii_case(&action)) {
+                distinct.push(action);
+            }
+        }
+
+        let mut resources: Vec<String> = Vec::new();
+        for resource in statements
+            .iter()
+            .flat_map(|s| s.resource.iter().chain(s.not_resource.iter()))
+            .flat_map(|resource| instances(resource))
+            .chain(["arn:aws:maip:::probe".to_string()])
+        {
+            if !resources.contains(&resource) {
+                resources.push(resource);
+            }
+        }
+
+        let mut principals: Vec<PrincipalAtom> = vec![None];
+        let mut conditions: Vec<KeyConditions> = Vec::new();
+        for statement in statements.iter() {
+            for principal in [&statement.principal, &statement.not_principal]
+                .into_iter()
+                .flatten()
+            {
+                if let Principal::Specific(principal) = principal {
+                    for (principal_type, values) in principal {
+                        for value in values {
+                            let atom = Some((*principal_type, value.clone()));
+                            if !principals.contains(&atom) {
+                                principals.push(atom);
+                            }
+                        }
+                    }
+                }
+            }
+
+            for condition_statement in statement.condition.iter() {
+                for condition in condition_statement.conditions.iter() {
+                    let operator = (condition_statement.operator, condition.values.as_slice());
+                    match conditions
+                        .iter_mut()
+                        .find(|(key, _)| key.eq_ignore_ascii_case(&condition.key))
+                    {
+                        Some((_, operators)) => operators.push(operator),
+                        None => conditions.push((condition.key.clone(), vec![operator])),
+                    }
+                }
+            }
+        }
+        let keys = conditions
+            .into_iter()
+            .map(|(name, operators)| ConditionKey {
+                name,
+                values: request_values(&operators),
+            })
+            .collect();
+
+        Self {
+            actions: distinct,
+            resources,
+            principals,
+            keys,
+        }
+    }
+
+    /// The number of requests, saturating at `usize::MAX`.
+    fn requests(&self) -> usize {
+        self.keys
+            .iter()
+            .map(|key| key.values.len())
+            .chain([
+                self.principals.len(),
+                self.actions.len(),
+                self.resources.len(),
+            ])
+            .fold(1, usize::saturating_mul)
+    }
+
+    /// Moves on to the next combination of condition values, returning false
+    /// once every combination was visited.
+    fn next_context(&self, context: &mut [usize]) -> bool {
+        for (index, key) in context.iter_mut().zip(self.keys.iter()) {
+            *index += 1;
+            if *index < key.values.len() {
+                return true;
+            }
+            *index = 0;
+        }
+        false
+    }
+
+    fn key(&self, name: &str) -> Option<usize> {
+        self.keys
+            .iter()
+            .position(|key| key.name.eq_ignore_ascii_case(name))
+    }
+
+    fn describe_principal(&self, principal: usize) -> Option<String> {
+        if self.principals.len() < 2 {
+            return None;
+        }
+        Some(match &self.principals[principal] {
+            Some((principal_type, value)) => format!("{} {}", principal_type, value),
+            None => "another principal".to_string(),
+        })
+    }
+
+    fn describe_context(&self, context: &[usize]) -> Vec<String> {
+        self.keys
+            .iter()
+            .zip(context.iter())
+            .map(|(key, index)| match &key.values[*index] {
+                RequestValue::Absent => format!("no {}", key.name),
+                RequestValue::Values(values) if values.len() == 1 => {
+                    format!("{} = {}", key.name, values[0])
+                }
+                RequestValue::Values(values) => {
+                    format!("{} = [{}]", key.name, values.join(", "))
+                }
+            })
+            .collect()
+    }
+}
+
+/// The statements of one side with their actions and resources matched
+/// against the universe up front.
+struct Side<'a> {
+    statements: Vec<&'a PolicyStatement>,
+    actions: Vec<Vec<bool>>,
+    resources: Vec<Vec<bool>>,
+}
+
+impl<'a> Side<'a> {
+    fn new(statements: &[&'a PolicyStatement], universe: &Universe) -> Self {
+        let actions = statements
+            .iter()
+            .map(|statement| {
+                universe
+                    .actions
+                    .iter()
+                    .map(|action| {
+                        matches(
+                            &statement.action,
+                            &statement.not_action,
+                            action,
+                            action_covers,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        let resources = statements
+            .iter()
+            .map(|statement| {
+                universe
+                    .resources
+                    .iter()
+                    .map(|resource| {
+                        matches(
+                            &statement.resource,
+                            &statement.not_resource,
+                            resource,
+                            resource_covers,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            statements: statements.to_vec(),
+            actions,
+            resources,
+        }
+    }
+
+    /// The statements applying to the principal and condition values.
+    fn active(&self, universe: &Universe, principal: usize, context: &[usize]) -> Vec<usize> {
+        let principal = &universe.principals[principal];
+        (0..self.statements.len())
+            .filter(|&index| {
+                let statement = self.statements[index];
+                applies_to(statement, principal) && conditions_hold(statement, universe, context)
+            })
+            .collect()
+    }
+
+    fn decide(&self, active: &[usize], action: usize, resource: usize) -> Decision {
+        let mut decision = Decision::ImplicitDeny;
+        for &index in active {
+            if !self.actions[index][action] || !self.resources[index][resource] {
+                continue;
+            }
+            if self.statements[index].effect == "Deny" {
+                return Decision::Deny;
+            }
+            decision = Decision::Allow;
+        }
+        decision
+    }
+}
+
+fn matches(
+    patterns: &[String],
+    not_patterns: &[String],
+    value: &str,
+    covers: fn(&str, &str) -> bool,
+) -> bool {
+    if !patterns.is_empty() {
+        patterns.iter().any(|pattern| covers(pattern, value))
+    } else {
+        !not_patterns.iter().any(|pattern| covers(pattern, value))
+    }
+}
+
+fn applies_to(statement: &PolicyStatement, principal: &PrincipalAtom) -> bool {
+    let named = match statement.principal.as_ref() {
+        Some(p) => names_principal(p, principal),
+        None => true,
+    };
+    let excluded = match statement.not_principal.as_ref() {
+        Some(p) => names_principal(p, principal),
+        None => false,
+    };
+    named && !excluded
+}
+
+fn names_principal(principal: &Principal, atom: &PrincipalAtom) -> bool {
+    match (principal, atom) {
+        (Principal::Any, _) => true,
+        (Principal::Specific(principals), Some((principal_type, value))) => principals
+            .get(principal_type)
+            .is_some_and(|values| values.iter().any(|v| v == value || v == "*")),
+        (Principal::Specific(_), None) => false,
+    }
+}
+
+fn conditions_hold(statement: &PolicyStatement, universe: &Universe, context: &[usize]) -> bool {
+    statement.condition.iter().all(|condition_statement| {
+        condition_statement.conditions.iter().all(|condition| {
+            let Some(index) = universe.key(&condition.key) else {
+                return false;
+            };
+            condition_holds(
+                &condition_statement.operator,
+                &condition.values,
+                &universe.keys[index].values[context[index]],
+            )
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{
+        merge_policy_documents, Condition, ConditionOperatorKind, ConditionStatement,
+    };
+    use crate::test_util::{document, statement};
+
+    fn counterexample(result: Result<(), VerifyError>) -> Counterexample {
+        match result {
+            Err(VerifyError::NotEquivalent(counterexample)) => counterexample,
+            result => panic!("Expected a counterexample, got {:?}", result),
+        }
+    }
+
+    fn string_equals(key: &str, value: &str) -> Vec<ConditionStatement> {
+        vec![ConditionStatement::new(
+            ConditionOperator::new(ConditionOperatorKind::StringEquals),
+            vec![Condition::new(key.to_string(), vec![value.to_string()])],
+        )]
+    }
+
+    #[test]
+    fn test_merge_is_equivalent() {
+        let inputs = [
+            document(vec![statement(
+                "Allow",
+                &["s3:GetObject"],
+                &["arn:aws:s3:::first/*"],
+            )]),
+            document(vec![
+                statement("Allow", &["s3:GetObject"], &["arn:aws:s3:::second/*"]),
+                statement("Deny", &["s3:*"], &["arn:aws:s3:::second/secret"]),
+            ]),
+        ];
+
+        let merged = merge_policy_documents(&inputs).unwrap();
+
+        assert_eq!(verify_equivalence(&inputs, &[merged], &[], &[]), Ok(()));
+    }
+
+    #[test]
+    fn test_broader_wildcard_is_found() {
+        let inputs = [document(vec![statement(
+            "Allow",
+            &["s3:GetObject"],
+            &["arn:aws:s3:::bucket/*"],
+        )])];
+        let merged = [document(vec![statement(
+            "Allow",
+            &["s3:Get*"],
+            &["arn:aws:s3:::bucket/*"],
+        )])];
+
+        let counterexample = counterexample(verify_equivalence(
+            &inputs,
+            &merged,
+            &["s3:GetObjectAcl".to_string()],
+            &[],
+        ));
+
+        assert_eq!(
+            counterexample.to_string(),
+            "s3:GetObjectAcl on arn:aws:s3:::bucket/probe: expected implicit deny, got allow"
+        );
+    }
+
+    #[test]
+    fn test_lost_deny_is_found() {
+        let inputs = [document(vec![
+            statement("Allow", &["s3:*"], &["*"]),
+            statement("Deny", &["s3:DeleteBucket"], &["*"]),
+        ])];
+        let merged = [document(vec![statement("Allow", &["s3:*"], &["*"])])];
+
+        let counterexample = counterexample(verify_equivalence(&inputs, &merged, &[], &[]));
+
+        assert_eq!(counterexample.action, "s3:DeleteBucket");
+        assert_eq!(counterexample.expected, Decision::Deny);
+        assert_eq!(counterexample.actual, Decision::Allow);
+    }
+
+    #[test]
+    fn test_condition_values_are_compared() {
+        let allow = |vpc: &str| {
+            PolicyStatement::new(
+                "Allow".to_string(),
+                vec!["s3:GetObject".to_string()],
+                vec!["*".to_string()],
+                string_equals("aws:SourceVpc", vpc),
+            )
+        };
+        let inputs = [
+            document(vec![allow("vpc-1")]),
+            document(vec![allow("vpc-2")]),
+        ];
+
+        let merged = merge_policy_documents(&inputs).unwrap();
+        assert_eq!(verify_equivalence(&inputs, &[merged], &[], &[]), Ok(()));
+
+        let counterexample = counterexample(verify_equivalence(
+            &inputs,
+            &[document(vec![allow("vpc-1")])],
+            &[],
+            &[],
+        ));
+        assert_eq!(counterexample.conditions, vec!["aws:SourceVpc = vpc-2"]);
+    }
+
+    #[test]
+    fn test_broader_merged_resource_is_found() {
+        let inputs = [document(vec![statement(
+            "Allow",
+            &["s3:GetObject"],
+            &["arn:aws:s3:::bucket/*"],
+        )])];
+        let merged = [document(vec![statement(
+            "Allow",
+            &["s3:GetObject"],
+            &["arn:aws:s3:::*"],
+        )])];
+
+        let counterexample = counterexample(verify_equivalence(&inputs, &merged, &[], &[]));
+
+        assert_eq!(
+            counterexample.to_string(),
+            "s3:GetObject on arn:aws:s3:::probe: expected implicit deny, got allow"
+        );
+    }
+
+    #[test]
+    fn test_wildcard_of_unknown_service_is_found() {
+        let inputs = [document(vec![statement(
+            "Allow",
+            &["ec2:DescribeInstances"],
+            &["*"],
+        )])];
+        let merged = [document(vec![statement("Allow", &["ec2:*"], &["*"])])];
+
+        let counterexample = counterexample(verify_equivalence(&inputs, &merged, &[], &[]));
+
+        assert_eq!(counterexample.action, "ec2:probe");
+        assert_eq!(counterexample.expected, Decision::ImplicitDeny);
+        assert_eq!(counterexample.actual, Decision::Allow);
+    }
+
+    #[test]
+    fn test_conditions_on_one_key_are_evaluated_together() {
+        let condition = |kind: ConditionOperatorKind, value: &str| {
+            ConditionStatement::new(
+                ConditionOperator::new(kind),
+                vec![Condition::new(
+                    "aws:SourceVpc".to_string(),
+                    vec![value.to_string()],
+                )],
+            )
+        };
+        let allow = |conditions: Vec<ConditionStatement>| {
+            document(vec![PolicyStatement::new(
+                "Allow".to_string(),
+                vec!["s3:GetObject".to_string()],
+                vec!["*".to_string()],
+                conditions,
+            )])
+        };
+        let inputs = [allow(vec![
+            condition(ConditionOperatorKind::StringLike, "vpc-*"),
+            condition(ConditionOperatorKind::StringNotEquals, "vpc-2"),
+        ])];
+        let merged = [allow(vec![condition(
+            ConditionOperatorKind::StringLike,
+            "vpc-*",
+        )])];
+
+        assert_eq!(verify_equivalence(&inputs, &inputs, &[], &[]), Ok(()));
+        let counterexample = counterexample(verify_equivalence(&inputs, &merged, &[], &[]));
+        assert_eq!(counterexample.conditions, vec!["aws:SourceVpc = vpc-2"]);
+    }
+
+    #[test]
+    fn test_complete_services_are_not_probed() {
+        let inputs = [document(vec![statement(
+            "Allow",
+            &["sts:AssumeRole", "sts:AssumeRoleWithSAML"],
+            &["*"],
+        )])];
+        let merged = [document(vec![statement("Allow", &["sts:A*"], &["*"])])];
+        let known = [
+            "sts:AssumeRole".to_string(),
+            "sts:AssumeRoleWithSAML".to_string(),
+        ];
+
+        assert_eq!(
+            verify_equivalence(&inputs, &merged, &known, &["sts".to_string()]),
+            Ok(())
+        );
+        assert!(verify_equivalence(&inputs, &merged, &known, &[]).is_err());
+    }
+
+    #[test]
+    fn test_too_many_requests_are_reported() {
+        let conditions = (0..12)
+            .map(|key| {
+                ConditionStatement::new(
+                    ConditionOperator::new(ConditionOperatorKind::StringEquals),
+                    vec![Condition::new(
+                        format!("aws:PrincipalTag/tag-{}", key),
+                        (0..8).map(|value| format!("value-{}", value)).collect(),
+                    )],
+                )
+            })
+            .collect();
+        let inputs = [document(vec![PolicyStatement::new(
+            "Allow".to_string(),
+            vec!["s3:GetObject".to_string()],
+            vec!["*".to_string()],
+            conditions,
+        )])];
+
+        let result = verify_equivalence(&inputs, &inputs, &[], &[]);
+
+        assert!(matches!(result, Err(VerifyError::TooLarge { .. })));
+    }
+}
@@ -0,0 +1,338 @@
+/// Returns true if the action pattern matches every action matched by `action`,
+/// which may itself contain wildcards.
+///
+/// Actions are matched case-insensitively, `*` matches any number of characters
+/// and `?` a single character anywhere in the action.
+pub fn action_covers(pattern: &str, action: &str) -> bool {
+    Glob::action(pattern).covers(&Glob::action(action))
+}
+
+/// Returns true if the resource pattern matches every resource matched by
+/// `resource`, which may itself contain wildcards.
+///
+/// Resources are matched like IAM matches ARNs: `*` matches any number of
+/// characters and `?` a single character within one colon separated segment.
+/// A `*` in the resource part of the ARN, after the fifth colon, or in the last
+/// segment of the pattern may also match across colons.
+pub fn resource_covers(pattern: &str, resource: &str) -> bool {
+    Glob::resource(pattern).covers(&Glob::resource(resource))
+}
+
+/// Returns true if the value matches the pattern of a `StringLike` condition,
+/// where `*` matches any number of characters and `?` a single one. The value
+/// is taken literally and the match is case-sensitive.
+pub fn string_like(pattern: &str, value: &str) -> bool {
+    let value = Glob {
+        tokens: value.chars().map(Token::Char).collect(),
+        segmented: false,
+    };
+    Glob::new(pattern, false).covers(&value)
+}
+
+/// The pattern itself if it has no wildcards, otherwise two values it
+/// matches: one with every `*` replaced by `probe` and one with every `*`
+/// left out.
+pub fn instances(pattern: &str) -> Vec<String> {
+    if !pattern.contains(['*', '?']) {
+        return vec![pattern.to_string()];
+    }
+    vec![
+        pattern.replace('*', "probe").replace('?', "x"),
+        pattern.replace('*', "").replace('?', "x"),
+    ]
+}
+
+/// Returns true if one of the patterns covers the action.
+pub fn any_action_covers(patterns: &[String], action: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| action_covers(pattern, action))
+}
+
+/// Returns true if one of the patterns covers the resource.
+pub fn any_resource_covers(patterns: &[String], resource: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| resource_covers(pattern, resource))
+}
+
+/// Returns true if both lists match exactly the same actions.
+pub fn same_actions(first: &[String], second: &[String]) -> bool {
+    first == second || equivalent(first, second, action_covers)
+}
+
+/// Returns true if both lists match exactly the same resources.
+pub fn same_resources(first: &[String], second: &[String]) -> bool {
+    first == second || equivalent(first, second, resource_covers)
+}
+
+/// Adds the actions of `other` that are not already in `actions`, ignoring case.
+pub fn merge_actions(actions: &mut Vec<String>, other: Vec<String>) {
+    for action in other {
+        if !actions.iter().any(|a| a.eq_ignore_ascii_case(&action)) {
+            actions.push(action);
+        }
+    }
+}
+
+/// Removes every action that is covered by another action of the list.
+pub fn reduce_actions(actions: &mut Vec<String>) {
+    reduce(actions, action_covers);
+}
+
+/// Removes every resource that is covered by another resource of the list.
+pub fn reduce_resources(resources: &mut Vec<String>) {
+    reduce(resources, resource_covers);
+}
+
+fn reduce(patterns: &mut Vec<String>, covers: fn(&str, &str) -> bool) {
+    let mut reduced: Vec<String> = Vec::new();
+    for pattern in patterns.drain(..) {
+        if reduced.iter().any(|other| covers(other, &pattern)) {
+            continue;
+        }
+        reduced.retain(|other| !covers(&pattern, other));
+        reduced.push(pattern);
+    }
+    *patterns = reduced;
+}
+
+fn equivalent(first: &[String], second: &[String], covers: fn(&str, &str) -> bool) -> bool {
+    let covered_by = |patterns: &[String], others: &[String]| {
+        others
+            .iter()
+            .all(|other| patterns.iter().any(|pattern| covers(pattern, other)))
+    };
+    covered_by(first, second) && covered_by(second, first)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Token {
+    Char(char),
+    /// Any single character, except a colon if the pattern is segmented.
+    AnyChar,
+    /// Any sequence of characters. The flag is set if it may match colons.
+    AnySequence(bool),
+}
+
+struct Glob {
+    tokens: Vec<Token>,
+    segmented: bool,
+}
+
+impl Glob {
+    fn action(pattern: &str) -> Self {
+        Self::new(&pattern.to_lowercase(), false)
+    }
+
+    fn resource(pattern: &str) -> Self {
+        Self::new(pattern, true)
+    }
+
+    fn new(pattern: &str, segmented: bool) -> Self {
+        let chars = pattern.chars().collect::<Vec<char>>();
+        let tokens = chars
+            .iter()
+            .enumerate()
+            .map(|(index, c)| match c {
+                '*' => {
+                    let resource_part = chars[..index].iter().filter(|c| **c == ':').count() >= 5;
+                    let last_segment = !chars[index..].contains(&':');
+                    Token::AnySequence(!segmented || resource_part || last_segment)
+                }
+                '?' => Token::AnyChar,
+                c => Token::Char(*c),
+            })
+            .collect();
+        Self { tokens, segmented }
+    }
+
+    /// Returns true if every string matched by `other` is matched by `self`.
+    ///
+    /// Wildcards of `other` are only covered by wildcards of `self` that match
+    /// at least the same strings, so the check never reports a cover that does
+    /// not hold, but may miss some exotic ones.
+    fn covers(&self, other: &Glob) -> bool {
+        let mut memo = vec![None; (self.tokens.len() + 1) * (other.tokens.len() + 1)];
+        self.covers_from(other, 0, 0, &mut memo)
+    }
+
+    fn covers_from(&self, other: &Glob, i: usize, j: usize, memo: &mut [Option<bool>]) -> bool {
+        let key = i * (other.tokens.len() + 1) + j;
+        if let Some(result) = memo[key] {
+            return result;
+        }
+
+        let result = match self.tokens.get(i) {
+            None => j == other.tokens.len(),
+            Some(Token::AnySequence(spans_colons)) => {
+                self.covers_from(other, i + 1, j, memo)
+                    || (j < other.tokens.len()
+                        && self.sequence_covers(*spans_colons, other, j)
+                        && self.covers_from(other, i, j + 1, memo))
+            }
+            Some(Token::AnyChar) => match other.tokens.get(j) {
+                Some(Token::Char(c)) => {
+                    !(self.segmented && *c == ':') && self.covers_from(other, i + 1, j + 1, memo)
+                }
+                Some(Token::AnyChar) => {
+                    (self.segmented <= other.segmented)
+                        && self.covers_from(other, i + 1, j + 1, memo)
+                }
+                _ => false,
+            },
+            Some(Token::Char(c)) => match other.tokens.get(j) {
+                Some(Token::Char(other_c)) => {
+                    c == other_c && self.covers_from(other, i + 1, j + 1, memo)
+                }
+                _ => false,
+            },
+        };
+
+        memo[key] = Some(result);
+        result
+    }
+
+    /// Returns true if a `*` of this pattern can consume the token of `other` at `j`.
+    fn sequence_covers(&self, spans_colons: bool, other: &Glob, j: usize) -> bool {
+        match other.tokens[j] {
+            Token::Char(c) => spans_colons || c != ':',
+            Token::AnyChar => spans_colons || other.segmented,
+            Token::AnySequence(other_spans_colons) => spans_colons || !other_spans_colons,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_covers_object_in_bucket() {
+        assert!(resource_covers(
+            "arn:aws:s3:::bucket/*",
+            "arn:aws:s3:::bucket/logs/app.log"
+        ));
+        assert!(resource_covers(
+            "arn:aws:s3:::bucket/*",
+            "arn:aws:s3:::bucket/logs/*"
+        ));
+        assert!(!resource_covers(
+            "arn:aws:s3:::bucket/logs/*",
+            "arn:aws:s3:::bucket/*"
+        ));
+        assert!(!resource_covers(
+            "arn:aws:s3:::bucket/*",
+            "arn:aws:s3:::bucket"
+        ));
+    }
+
+    #[test]
+    fn test_resource_covers_single_character() {
+        assert!(resource_covers(
+            "arn:aws:s3:::bucket-?/*",
+            "arn:aws:s3:::bucket-a/key"
+        ));
+        assert!(!resource_covers(
+            "arn:aws:s3:::bucket-?/*",
+            "arn:aws:s3:::bucket-ab/key"
+        ));
+    }
+
+    #[test]
+    fn test_resource_covers_is_segment_aware() {
+        assert!(resource_covers(
+            "arn:aws:ec2:*:123456789012:instance/*",
+            "arn:aws:ec2:us-east-1:123456789012:instance/i-123"
+        ));
+        assert!(!resource_covers(
+            "arn:aws:ec2:us-*-1:123456789012:instance/*",
+            "arn:aws:ec2:us-east:1:123456789012:instance/i-123"
+        ));
+        assert!(resource_covers("*", "arn:aws:s3:::bucket/*"));
+        assert!(!resource_covers("arn:aws:s3:::*", "*"));
+    }
+
+    #[test]
+    fn test_resource_covers_spans_colons_only_in_resource_part() {
+        assert!(resource_covers(
+            "arn:aws:logs:*:123456789012:log-group:*",
+            "arn:aws:logs:eu-west-1:123456789012:log-group:app:log-stream:web"
+        ));
+        assert!(resource_covers(
+            "arn:aws:logs:eu-west-1:123456789012:log-group:app*:*",
+            "arn:aws:logs:eu-west-1:123456789012:log-group:app:log-stream:web"
+        ));
+        assert!(!resource_covers(
+            "arn:aws:logs:*:123456789012:log-group:*",
+            "arn:aws:logs:eu-west-1:999999999999:123456789012:log-group:app"
+        ));
+        assert!(!resource_covers(
+            "arn:aws:iam::*:role/admin",
+            "arn:aws:iam::123456789012:user/x:role/admin"
+        ));
+    }
+
+    #[test]
+    fn test_action_covers() {
+        assert!(action_covers("s3:Get*", "S3:getobject"));
+        assert!(action_covers("ec2:*Instances", "ec2:DescribeInstances"));
+        assert!(action_covers("s3:Get?bject", "s3:GetObject"));
+        assert!(action_covers("*", "s3:GetObject"));
+        assert!(action_covers("s3:Get*", "s3:GetObject*"));
+        assert!(!action_covers("s3:Get?bject", "s3:GetObjectAcl"));
+        assert!(!action_covers(
+            "ec2:*Instances",
+            "ec2:DescribeInstanceStatus"
+        ));
+        assert!(!action_covers("s3:GetObject", "s3:Get*"));
+    }
+
+    #[test]
+    fn test_same_actions() {
+        assert!(same_actions(
+            &["s3:*".to_string(), "s3:GetObject".to_string()],
+            &["S3:*".to_string()]
+        ));
+        assert!(!same_actions(
+            &["s3:Get*".to_string()],
+            &["s3:GetObject".to_string()]
+        ));
+    }
+
+    #[test]
+    fn test_reduce_actions() {
+        let mut actions = vec![
+            "s3:GetObject".to_string(),
+            "ec2:*Instances".to_string(),
+            "S3:Get*".to_string(),
+            "ec2:DescribeInstances".to_string(),
+            "s3:get*".to_string(),
+            "ec2:DescribeVpcs".to_string(),
+        ];
+
+        reduce_actions(&mut actions);
+
+        assert_eq!(
+            actions,
+            vec!["ec2:*Instances", "S3:Get*", "ec2:DescribeVpcs"]
+        );
+    }
+
+    #[test]
+    fn test_reduce_resources() {
+        let mut resources = vec![
+            "arn:aws:s3:::bucket/logs/app.log".to_string(),
+            "arn:aws:s3:::other-bucket".to_string(),
+            "arn:aws:s3:::bucket/*".to_string(),
+            "arn:aws:s3:::bucket/*".to_string(),
+        ];
+
+        reduce_resources(&mut resources);
+
+        assert_eq!(
+            resources,
+            vec!["arn:aws:s3:::other-bucket", "arn:aws:s3:::bucket/*"]
+        );
+    }
+}
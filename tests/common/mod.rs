@@ -0,0 +1,26 @@
+//! Builders for the policies used by the integration tests. Not every test
+//! uses every builder.
+#![allow(dead_code)]
+
+use maip::policy::{PolicyDocument, PolicyStatement};
+
+pub fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+pub fn statement(effect: &str, action: &[&str], resource: &[&str]) -> PolicyStatement {
+    PolicyStatement::new(
+        effect.to_string(),
+        strings(action),
+        strings(resource),
+        Vec::new(),
+    )
+}
+
+pub fn allow(action: &[&str], resource: &[&str]) -> PolicyStatement {
+    statement("Allow", action, resource)
+}
+
+pub fn document(statements: Vec<PolicyStatement>) -> PolicyDocument {
+    PolicyDocument::new("2012-10-17".to_string(), statements)
+}
@@ -0,0 +1,94 @@
+use maip::error::MaipError;
+use maip::policy::{
+    merge_policy_documents_with_options, ConflictPolicy, MergeOptions, PolicyDocument,
+    PolicyStatement, VERSION_2012_10_17,
+};
+
+fn conflicting_documents() -> Vec<PolicyDocument> {
+    vec![
+        PolicyDocument::new(
+            VERSION_2012_10_17.to_string(),
+            vec![PolicyStatement::new(
+                "Allow".to_string(),
+                vec!["s3:DeleteBucket".to_string()],
+                vec!["*".to_string()],
+                Vec::new(),
+            )],
+        ),
+        PolicyDocument::new(
+            VERSION_2012_10_17.to_string(),
+            vec![PolicyStatement::new(
+                "Deny".to_string(),
+                vec!["s3:DeleteBucket".to_string()],
+                vec!["*".to_string()],
+                Vec::new(),
+            )],
+        ),
+    ]
+}
+
+#[test]
+fn test_conflict_deny_wins() {
+    let options = MergeOptions {
+        conflict_policy: ConflictPolicy::DenyWins,
+        ..Default::default()
+    };
+
+    let outcome = merge_policy_documents_with_options(&conflicting_documents(), &options).unwrap();
+
+    assert_eq!(outcome.conflicts.len(), 1);
+    assert_eq!(outcome.document.statement.len(), 1);
+    assert_eq!(outcome.document.statement[0].effect, "Deny");
+}
+
+#[test]
+fn test_conflict_keep_both() {
+    let options = MergeOptions {
+        conflict_policy: ConflictPolicy::KeepBoth,
+        ..Default::default()
+    };
+
+    let outcome = merge_policy_documents_with_options(&conflicting_documents(), &options).unwrap();
+
+    assert_eq!(outcome.conflicts.len(), 1);
+    assert_eq!(outcome.document.statement.len(), 2);
+}
+
+#[test]
+fn test_conflict_fail() {
+    let options = MergeOptions {
+        conflict_policy: ConflictPolicy::Fail,
+        ..Default::default()
+    };
+
+    let result = merge_policy_documents_with_options(&conflicting_documents(), &options);
+
+    assert!(matches!(result, Err(MaipError::MergeConflict(_))));
+}
+
+#[test]
+fn test_no_conflict_for_partial_overlap() {
+    let documents = vec![PolicyDocument::new(
+        VERSION_2012_10_17.to_string(),
+        vec![
+            PolicyStatement::new(
+                "Allow".to_string(),
+                vec!["s3:*".to_string()],
+                vec!["*".to_string()],
+                Vec::new(),
+            ),
+            PolicyStatement::new(
+                "Deny".to_string(),
+                vec!["s3:DeleteBucket".to_string()],
+                vec!["*".to_string()],
+                Vec::new(),
+            ),
+        ],
+    )];
+
+    let outcome =
+        merge_policy_documents_with_options(&documents, &MergeOptions::default()).unwrap();
+
+    assert!(outcome.conflicts.is_empty());
+    assert_eq!(outcome.document.statement.len(), 2);
+}
@@ -1,7 +1,11 @@
+mod common;
+
 use std::fs::read_to_string;
 
+use common::{allow, document};
 use maip::policy::{
-    merge_policy_documents, Condition, ConditionStatement, PolicyDocument, PolicyStatement,
+    merge_policy_documents, merge_policy_documents_with_options, Condition, ConditionStatement,
+    MergeOptions, MergeStrategy, PolicyDocument, PolicyStatement,
 };
 
 #[test]
@@ -71,14 +75,14 @@ fn test_merge_ec2_and_rds_policy() {
                 vec!["*".to_string()],
                 vec![
                     ConditionStatement::new(
-                        "ForAllValues:StringEquals".to_string(),
+                        "ForAllValues:StringEquals".parse().unwrap(),
                         vec![Condition::new(
                             "devops-guru:ServiceNames".to_string(),
                             vec!["RDS".to_string()],
                         )],
                     ),
                     ConditionStatement::new(
-                        "Null".to_string(),
+                        "Null".parse().unwrap(),
                         vec![Condition::new(
                             "devops-guru:ServiceNames".to_string(),
                             vec!["false".to_string()],
@@ -91,7 +95,7 @@ fn test_merge_ec2_and_rds_policy() {
                 vec!["iam:CreateServiceLinkedRole".to_string()],
                 vec!["*".to_string()],
                 vec![ConditionStatement::new(
-                    "StringLike".to_string(),
+                    "StringLike".parse().unwrap(),
                     vec![Condition::new(
                         "iam:AWSServiceName".to_string(),
                         vec![
@@ -106,7 +110,7 @@ fn test_merge_ec2_and_rds_policy() {
                 vec!["iam:CreateServiceLinkedRole".to_string()],
                 vec!["*".to_string()],
                 vec![ConditionStatement::new(
-                    "StringEquals".to_string(),
+                    "StringEquals".parse().unwrap(),
                     vec![Condition::new(
                         "iam:AWSServiceName".to_string(),
                         vec![
@@ -144,3 +148,110 @@ fn test_merge_ec2_and_rds_policy() {
         expected_policy_document.size()
     )
 }
+
+#[test]
+fn test_merge_output_is_deterministic() {
+    let merge = || {
+        let ec2_policy = read_to_string("./tests/assets/AmazonEC2FullAccessPolicy.json").unwrap();
+        let ec2_policy: PolicyDocument = serde_json::from_str(&ec2_policy).unwrap();
+
+        let rds_policy = read_to_string("./tests/assets/AmazonRDSFullAccessPolicy.json").unwrap();
+        let rds_policy: PolicyDocument = serde_json::from_str(&rds_policy).unwrap();
+
+        let merged_policy_document = merge_policy_documents(&[ec2_policy, rds_policy]).unwrap();
+        serde_json::to_string(&merged_policy_document).unwrap()
+    };
+
+    let first_run = merge();
+
+    (0..10).for_each(|_| assert_eq!(merge(), first_run));
+}
+
+fn merge_with_strategy(documents: &[PolicyDocument], strategy: MergeStrategy) -> PolicyDocument {
+    let options = MergeOptions {
+        strategy,
+        ..Default::default()
+    };
+    let mut document = merge_policy_documents_with_options(documents, &options)
+        .unwrap()
+        .document;
+    document.sort();
+    document
+}
+
+#[test]
+fn test_merge_optimal_is_smaller_than_greedy() {
+    let documents = [
+        document(vec![
+            allow(&["s3:GetObject"], &["arn:aws:s3:::a/*"]),
+            allow(&["s3:PutObject"], &["arn:aws:s3:::b/*"]),
+        ]),
+        document(vec![
+            allow(&["s3:GetObject"], &["arn:aws:s3:::b/*"]),
+            allow(&["s3:PutObject"], &["arn:aws:s3:::a/*"]),
+        ]),
+    ];
+
+    let greedy = merge_with_strategy(&documents, MergeStrategy::Greedy);
+    let optimal = merge_with_strategy(&documents, MergeStrategy::Optimal);
+
+    assert_eq!(greedy.statement.len(), 2);
+    assert_eq!(
+        optimal.statement,
+        vec![allow(
+            &["s3:GetObject", "s3:PutObject"],
+            &["arn:aws:s3:::a/*", "arn:aws:s3:::b/*"]
+        )]
+    );
+    assert!(optimal.size() < greedy.size());
+}
+
+#[test]
+fn test_merge_optimal_does_not_depend_on_input_order() {
+    let ec2_policy = read_to_string("./tests/assets/AmazonEC2FullAccessPolicy.json").unwrap();
+    let ec2_policy: PolicyDocument = serde_json::from_str(&ec2_policy).unwrap();
+
+    let rds_policy = read_to_string("./tests/assets/AmazonRDSFullAccessPolicy.json").unwrap();
+    let rds_policy: PolicyDocument = serde_json::from_str(&rds_policy).unwrap();
+
+    let greedy = merge_with_strategy(
+        &[ec2_policy.clone(), rds_policy.clone()],
+        MergeStrategy::Greedy,
+    );
+    let optimal = merge_with_strategy(
+        &[ec2_policy.clone(), rds_policy.clone()],
+        MergeStrategy::Optimal,
+    );
+    let reversed = merge_with_strategy(&[rds_policy, ec2_policy], MergeStrategy::Optimal);
+
+    assert_eq!(optimal, reversed);
+    assert!(optimal.size() <= greedy.size());
+}
+
+#[test]
+fn test_merge_makes_colliding_sids_unique() {
+    let documents = [
+        document(vec![
+            allow(&["s3:GetObject"], &["arn:aws:s3:::a/*"]).with_sid("VisualEditor0")
+        ]),
+        document(vec![
+            allow(&["sqs:SendMessage"], &["arn:aws:sqs:*:*:queue"]).with_sid("VisualEditor0"),
+            allow(&["sns:Publish"], &["arn:aws:sns:*:*:topic"]).with_sid("VisualEditor1"),
+        ]),
+    ];
+
+    for strategy in [MergeStrategy::Greedy, MergeStrategy::Optimal] {
+        let merged = merge_with_strategy(&documents, strategy);
+
+        let mut sids: Vec<String> = merged
+            .statement
+            .iter()
+            .filter_map(|statement| statement.sid.clone())
+            .collect();
+        sids.sort();
+        assert_eq!(
+            sids,
+            vec!["VisualEditor0", "VisualEditor01", "VisualEditor1"]
+        );
+    }
+}
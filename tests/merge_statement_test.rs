@@ -1,4 +1,7 @@
-use maip::policy::{merge_statements, Condition, ConditionStatement, PolicyStatement};
+use maip::policy::{
+    is_conflict, merge_statements, Condition, ConditionStatement, PolicyStatement, Principal,
+    PrincipalType,
+};
 
 #[test]
 fn test_merge_allow_different_action_same_resource() {
@@ -136,13 +139,8 @@ fn test_merge_allow_and_deny_same() {
     );
     let merged_statement = merge_statements(&first_statement, &second_statement);
 
-    let expected_statement = PolicyStatement::new(
-        "Deny".to_string(),
-        vec!["ec2:Describe*".to_string()],
-        vec!["*".to_string()],
-        Vec::new(),
-    );
-    assert_eq!(merged_statement.unwrap(), expected_statement);
+    assert!(merged_statement.is_none());
+    assert!(is_conflict(&first_statement, &second_statement));
 }
 
 #[test]
@@ -190,7 +188,7 @@ fn test_merge_allow_all_same_with_condition() {
         vec!["ec2:*".to_string()],
         vec!["*".to_string()],
         vec![ConditionStatement::new(
-            "StringEquals".to_string(),
+            "StringEquals".parse().unwrap(),
             vec![Condition::new(
                 "ec2:Region".to_string(),
                 vec!["us-east-1".to_string()],
@@ -202,7 +200,7 @@ fn test_merge_allow_all_same_with_condition() {
         vec!["ec2:*".to_string()],
         vec!["*".to_string()],
         vec![ConditionStatement::new(
-            "StringEquals".to_string(),
+            "StringEquals".parse().unwrap(),
             vec![Condition::new(
                 "ec2:Region".to_string(),
                 vec!["us-east-1".to_string()],
@@ -211,7 +209,7 @@ fn test_merge_allow_all_same_with_condition() {
     );
 
     let merged_statement = merge_statements(&first_statement, &second_statement);
-    assert!(merged_statement.is_none());
+    assert_eq!(merged_statement.unwrap(), first_statement);
 }
 
 #[test]
@@ -228,14 +226,14 @@ fn test_merge_allow_all_same_one_has_condition() {
         vec!["*".to_string()],
         vec![
             ConditionStatement::new(
-                "StringEquals".to_string(),
+                "StringEquals".parse().unwrap(),
                 vec![Condition::new(
                     "ec2:Region".to_string(),
                     vec!["us-east-1".to_string()],
                 )],
             ),
             ConditionStatement::new(
-                "StringEquals".to_string(),
+                "StringEquals".parse().unwrap(),
                 vec![Condition::new(
                     "ec2:Region".to_string(),
                     vec!["us-east-2".to_string()],
@@ -313,3 +311,600 @@ fn merge_same_action_different_resources() {
     );
     assert_eq!(merged_statement.unwrap(), expected_statement);
 }
+
+#[test]
+fn test_merge_drops_different_sids() {
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["ec2:Describe*".to_string()],
+        vec!["*".to_string()],
+        Vec::new(),
+    )
+    .with_sid("AllowEc2");
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["rds:Describe*".to_string()],
+        vec!["*".to_string()],
+        Vec::new(),
+    )
+    .with_sid("AllowRds");
+
+    let merged_statement = merge_statements(&first_statement, &second_statement).unwrap();
+
+    assert_eq!(merged_statement.sid, None);
+}
+
+#[test]
+fn test_merge_keeps_sid_of_one_statement() {
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["ec2:Describe*".to_string()],
+        vec!["*".to_string()],
+        Vec::new(),
+    );
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["rds:Describe*".to_string()],
+        vec!["*".to_string()],
+        Vec::new(),
+    )
+    .with_sid("AllowRds");
+
+    let merged_statement = merge_statements(&first_statement, &second_statement).unwrap();
+
+    assert_eq!(merged_statement.sid, Some("AllowRds".to_string()));
+}
+
+#[test]
+fn test_merge_keeps_equal_sids() {
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["ec2:Describe*".to_string()],
+        vec!["*".to_string()],
+        Vec::new(),
+    )
+    .with_sid("Describe");
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["rds:Describe*".to_string()],
+        vec!["*".to_string()],
+        Vec::new(),
+    )
+    .with_sid("Describe");
+
+    let merged_statement = merge_statements(&first_statement, &second_statement).unwrap();
+
+    assert_eq!(merged_statement.sid, Some("Describe".to_string()));
+}
+
+#[test]
+fn test_merge_not_action_with_action() {
+    let first_statement = PolicyStatement::new(
+        "Deny".to_string(),
+        Vec::new(),
+        vec!["*".to_string()],
+        Vec::new(),
+    )
+    .with_not_action(vec!["iam:*".to_string()]);
+    let second_statement = PolicyStatement::new(
+        "Deny".to_string(),
+        vec!["s3:DeleteBucket".to_string()],
+        vec!["*".to_string()],
+        Vec::new(),
+    );
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    assert!(merged_statement.is_none());
+}
+
+#[test]
+fn test_merge_different_not_actions_same_resource() {
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        Vec::new(),
+        vec!["*".to_string()],
+        Vec::new(),
+    )
+    .with_not_action(vec!["iam:*".to_string()]);
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        Vec::new(),
+        vec!["*".to_string()],
+        Vec::new(),
+    )
+    .with_not_action(vec!["sts:*".to_string()]);
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    assert!(merged_statement.is_none());
+}
+
+#[test]
+fn test_merge_same_not_action_different_resource() {
+    let first_statement = PolicyStatement::new(
+        "Deny".to_string(),
+        Vec::new(),
+        vec!["arn:aws:s3:::first-bucket/*".to_string()],
+        Vec::new(),
+    )
+    .with_not_action(vec!["s3:GetObject".to_string()]);
+    let second_statement = PolicyStatement::new(
+        "Deny".to_string(),
+        Vec::new(),
+        vec!["arn:aws:s3:::second-bucket/*".to_string()],
+        Vec::new(),
+    )
+    .with_not_action(vec!["s3:GetObject".to_string()]);
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    let expected_statement = PolicyStatement::new(
+        "Deny".to_string(),
+        Vec::new(),
+        vec![
+            "arn:aws:s3:::first-bucket/*".to_string(),
+            "arn:aws:s3:::second-bucket/*".to_string(),
+        ],
+        Vec::new(),
+    )
+    .with_not_action(vec!["s3:GetObject".to_string()]);
+    assert_eq!(merged_statement.unwrap(), expected_statement);
+}
+
+#[test]
+fn test_merge_same_not_resource_different_action() {
+    let first_statement = PolicyStatement::new(
+        "Deny".to_string(),
+        vec!["s3:DeleteObject".to_string()],
+        Vec::new(),
+        Vec::new(),
+    )
+    .with_not_resource(vec!["arn:aws:s3:::scratch/*".to_string()]);
+    let second_statement = PolicyStatement::new(
+        "Deny".to_string(),
+        vec!["s3:PutObject".to_string()],
+        Vec::new(),
+        Vec::new(),
+    )
+    .with_not_resource(vec!["arn:aws:s3:::scratch/*".to_string()]);
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    let expected_statement = PolicyStatement::new(
+        "Deny".to_string(),
+        vec!["s3:DeleteObject".to_string(), "s3:PutObject".to_string()],
+        Vec::new(),
+        Vec::new(),
+    )
+    .with_not_resource(vec!["arn:aws:s3:::scratch/*".to_string()]);
+    assert_eq!(merged_statement.unwrap(), expected_statement);
+}
+
+#[test]
+fn test_merge_same_statement_different_principals() {
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        Vec::new(),
+    )
+    .with_principal(Principal::new(
+        PrincipalType::Aws,
+        vec!["arn:aws:iam::111122223333:root".to_string()],
+    ));
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        Vec::new(),
+    )
+    .with_principal(Principal::new(
+        PrincipalType::Aws,
+        vec!["arn:aws:iam::444455556666:root".to_string()],
+    ));
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    let expected_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        Vec::new(),
+    )
+    .with_principal(Principal::new(
+        PrincipalType::Aws,
+        vec![
+            "arn:aws:iam::111122223333:root".to_string(),
+            "arn:aws:iam::444455556666:root".to_string(),
+        ],
+    ));
+    assert_eq!(merged_statement.unwrap(), expected_statement);
+}
+
+#[test]
+fn test_merge_different_actions_different_principals() {
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        Vec::new(),
+    )
+    .with_principal(Principal::new(
+        PrincipalType::Aws,
+        vec!["arn:aws:iam::111122223333:root".to_string()],
+    ));
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:PutObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        Vec::new(),
+    )
+    .with_principal(Principal::new(
+        PrincipalType::Service,
+        vec!["cloudtrail.amazonaws.com".to_string()],
+    ));
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    assert!(merged_statement.is_none());
+}
+
+#[test]
+fn test_merge_statement_without_resource_keeps_it() {
+    let principal = || {
+        Principal::new(
+            PrincipalType::Aws,
+            vec!["arn:aws:iam::111122223333:root".to_string()],
+        )
+    };
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        Vec::new(),
+        Vec::new(),
+    )
+    .with_principal(principal());
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::b/x".to_string()],
+        Vec::new(),
+    )
+    .with_principal(principal());
+    let third_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:PutObject".to_string()],
+        Vec::new(),
+        Vec::new(),
+    )
+    .with_principal(principal());
+
+    assert!(merge_statements(&first_statement, &second_statement).is_none());
+    assert!(merge_statements(&second_statement, &first_statement).is_none());
+
+    let expected_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string(), "s3:PutObject".to_string()],
+        Vec::new(),
+        Vec::new(),
+    )
+    .with_principal(principal());
+    assert_eq!(
+        merge_statements(&first_statement, &third_statement).unwrap(),
+        expected_statement
+    );
+}
+
+#[test]
+fn test_merge_any_principal_absorbs_specific_principal() {
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        Vec::new(),
+    )
+    .with_principal(Principal::new(
+        PrincipalType::Aws,
+        vec!["arn:aws:iam::111122223333:root".to_string()],
+    ));
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        Vec::new(),
+    )
+    .with_principal(Principal::Any);
+
+    let merged_statement = merge_statements(&first_statement, &second_statement).unwrap();
+
+    assert_eq!(merged_statement.principal, Some(Principal::Any));
+}
+
+fn statement_with_conditions(condition: Vec<ConditionStatement>) -> PolicyStatement {
+    PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        condition,
+    )
+}
+
+#[test]
+fn test_merge_conditions_in_different_order() {
+    let first_statement = statement_with_conditions(vec![
+        ConditionStatement::new(
+            "StringEquals".parse().unwrap(),
+            vec![
+                Condition::new("aws:PrincipalTag/team".to_string(), vec!["ops"]),
+                Condition::new(
+                    "aws:RequestedRegion".to_string(),
+                    vec!["eu-west-1", "us-east-1"],
+                ),
+            ],
+        ),
+        ConditionStatement::new(
+            "Bool".parse().unwrap(),
+            vec![Condition::new(
+                "aws:SecureTransport".to_string(),
+                vec![true],
+            )],
+        ),
+    ]);
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:PutObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        vec![
+            ConditionStatement::new(
+                "Bool".parse().unwrap(),
+                vec![Condition::new(
+                    "aws:SecureTransport".to_string(),
+                    vec![true],
+                )],
+            ),
+            ConditionStatement::new(
+                "StringEquals".parse().unwrap(),
+                vec![
+                    Condition::new(
+                        "aws:RequestedRegion".to_string(),
+                        vec!["us-east-1", "eu-west-1"],
+                    ),
+                    Condition::new("aws:PrincipalTag/team".to_string(), vec!["ops"]),
+                ],
+            ),
+        ],
+    );
+
+    let merged_statement = merge_statements(&first_statement, &second_statement).unwrap();
+
+    assert_eq!(
+        merged_statement.action,
+        vec!["s3:GetObject", "s3:PutObject"]
+    );
+    assert_eq!(merged_statement.condition, first_statement.condition);
+}
+
+#[test]
+fn test_merge_condition_value_union() {
+    let first_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "StringEquals".parse().unwrap(),
+        vec![Condition::new(
+            "aws:RequestedRegion".to_string(),
+            vec!["eu-west-1"],
+        )],
+    )]);
+    let second_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "StringEquals".parse().unwrap(),
+        vec![Condition::new(
+            "aws:RequestedRegion".to_string(),
+            vec!["us-east-1"],
+        )],
+    )]);
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    let expected_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "StringEquals".parse().unwrap(),
+        vec![Condition::new(
+            "aws:RequestedRegion".to_string(),
+            vec!["eu-west-1", "us-east-1"],
+        )],
+    )]);
+    assert_eq!(merged_statement.unwrap(), expected_statement);
+}
+
+#[test]
+fn test_merge_condition_value_union_negated_operator() {
+    let first_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "StringNotEquals".parse().unwrap(),
+        vec![Condition::new(
+            "aws:RequestedRegion".to_string(),
+            vec!["eu-west-1"],
+        )],
+    )]);
+    let second_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "StringNotEquals".parse().unwrap(),
+        vec![Condition::new(
+            "aws:RequestedRegion".to_string(),
+            vec!["us-east-1"],
+        )],
+    )]);
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    assert!(merged_statement.is_none());
+}
+
+#[test]
+fn test_merge_condition_value_union_for_all_values() {
+    let first_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "ForAllValues:StringEquals".parse().unwrap(),
+        vec![Condition::new("aws:TagKeys".to_string(), vec!["team"])],
+    )]);
+    let second_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "ForAllValues:StringEquals".parse().unwrap(),
+        vec![Condition::new("aws:TagKeys".to_string(), vec!["env"])],
+    )]);
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    assert!(merged_statement.is_none());
+}
+
+#[test]
+fn test_merge_condition_values_of_two_keys() {
+    let first_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "StringEquals".parse().unwrap(),
+        vec![
+            Condition::new("aws:PrincipalTag/team".to_string(), vec!["ops"]),
+            Condition::new("aws:RequestedRegion".to_string(), vec!["eu-west-1"]),
+        ],
+    )]);
+    let second_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "StringEquals".parse().unwrap(),
+        vec![
+            Condition::new("aws:PrincipalTag/team".to_string(), vec!["dev"]),
+            Condition::new("aws:RequestedRegion".to_string(), vec!["us-east-1"]),
+        ],
+    )]);
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    assert!(merged_statement.is_none());
+}
+
+#[test]
+fn test_merge_condition_value_union_different_actions() {
+    let first_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "StringEquals".parse().unwrap(),
+        vec![Condition::new(
+            "aws:RequestedRegion".to_string(),
+            vec!["eu-west-1"],
+        )],
+    )]);
+    let mut second_statement = statement_with_conditions(vec![ConditionStatement::new(
+        "StringEquals".parse().unwrap(),
+        vec![Condition::new(
+            "aws:RequestedRegion".to_string(),
+            vec!["us-east-1"],
+        )],
+    )]);
+    second_statement.action = vec!["s3:PutObject".to_string()];
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    assert!(merged_statement.is_none());
+}
+
+#[test]
+fn test_merge_same_action_subsumed_resource() {
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/logs/app.log".to_string()],
+        Vec::new(),
+    );
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        Vec::new(),
+    );
+
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    let expected_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        Vec::new(),
+    );
+    assert_eq!(merged_statement.unwrap(), expected_statement);
+}
+
+#[test]
+fn test_merge_same_action_ignoring_case() {
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::first/*".to_string()],
+        Vec::new(),
+    );
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["S3:getobject".to_string()],
+        vec!["arn:aws:s3:::second/*".to_string()],
+        Vec::new(),
+    );
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    let expected_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec![
+            "arn:aws:s3:::first/*".to_string(),
+            "arn:aws:s3:::second/*".to_string(),
+        ],
+        Vec::new(),
+    );
+    assert_eq!(merged_statement, Some(expected_statement));
+}
+
+#[test]
+fn test_merge_same_resource_skips_actions_differing_in_case() {
+    let first_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["*".to_string()],
+        Vec::new(),
+    );
+    let second_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:getobject".to_string(), "s3:PutObject".to_string()],
+        vec!["*".to_string()],
+        Vec::new(),
+    );
+    let merged_statement = merge_statements(&first_statement, &second_statement);
+
+    let expected_statement = PolicyStatement::new(
+        "Allow".to_string(),
+        vec!["s3:GetObject".to_string(), "s3:PutObject".to_string()],
+        vec!["*".to_string()],
+        Vec::new(),
+    );
+    assert_eq!(merged_statement, Some(expected_statement));
+}
+
+#[test]
+fn test_sort_principal_and_not_principal() {
+    let principals = || {
+        Principal::new(
+            PrincipalType::Aws,
+            vec![
+                "arn:aws:iam::444455556666:root".to_string(),
+                "arn:aws:iam::111122223333:root".to_string(),
+            ],
+        )
+    };
+    let mut statement = PolicyStatement::new(
+        "Deny".to_string(),
+        vec!["s3:GetObject".to_string()],
+        vec!["arn:aws:s3:::bucket/*".to_string()],
+        Vec::new(),
+    )
+    .with_principal(principals())
+    .with_not_principal(principals());
+
+    statement.sort();
+
+    let sorted = Principal::new(
+        PrincipalType::Aws,
+        vec![
+            "arn:aws:iam::111122223333:root".to_string(),
+            "arn:aws:iam::444455556666:root".to_string(),
+        ],
+    );
+    assert_eq!(statement.principal, Some(sorted.clone()));
+    assert_eq!(statement.not_principal, Some(sorted));
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use common::{allow, document};
+use maip::policy::{
+    pack_policy_documents, Condition, ConditionStatement, MergeOptions, PolicyDocument,
+    PolicyStatement,
+};
+
+fn listed(action: &str, resource: &str, count: usize) -> PolicyStatement {
+    let resources: Vec<String> = (0..count).map(|i| format!("{}-{}", resource, i)).collect();
+    let resources: Vec<&str> = resources.iter().map(String::as_str).collect();
+    allow(&[action], &resources)
+}
+
+#[test]
+fn test_pack_places_documents_by_statement_origin() {
+    let shared = listed("s3:GetObject", "arn:aws:s3:::shared/object", 12);
+    let queues = listed(
+        "sqs:SendMessage",
+        "arn:aws:sqs:eu-west-1:123456789012:queue",
+        8,
+    );
+    let mut tagged_queues = queues.clone();
+    tagged_queues.condition = vec![ConditionStatement::new(
+        "StringEquals".parse().unwrap(),
+        vec![Condition::new(
+            "aws:ResourceTag/team".to_string(),
+            vec!["storage".to_string()],
+        )],
+    )];
+    let documents = [
+        document(vec![shared.clone(), queues]),
+        document(vec![
+            shared.clone(),
+            listed("sns:Publish", "arn:aws:sns:eu-west-1:123456789012:topic", 8),
+        ]),
+        document(vec![
+            shared,
+            listed(
+                "sqs:ReceiveMessage",
+                "arn:aws:sqs:eu-west-1:123456789012:inbox",
+                8,
+            ),
+        ]),
+        document(vec![tagged_queues]),
+    ];
+    let limit = documents.iter().map(PolicyDocument::size).max().unwrap();
+
+    let outcome = pack_policy_documents(&documents, 10, limit, &MergeOptions::default()).unwrap();
+
+    // The shared statement and the queues of the first document fill the
+    // first packed document. The queues with a condition are subsumed by the
+    // queues without one, so the last document is placed where those are.
+    assert_eq!(outcome.documents.len(), 2);
+    assert!(outcome.documents.iter().all(|d| d.size() <= limit));
+    assert_eq!(
+        outcome.placements,
+        vec![vec![0, 1], vec![0, 1], vec![0], vec![1]]
+    );
+}
+
+#[test]
+fn test_pack_ec2_and_rds_policy() {
+    let ec2_policy = read_to_string("./tests/assets/AmazonEC2FullAccessPolicy.json").unwrap();
+    let ec2_policy: PolicyDocument = serde_json::from_str(&ec2_policy).unwrap();
+
+    let rds_policy = read_to_string("./tests/assets/AmazonRDSFullAccessPolicy.json").unwrap();
+    let rds_policy: PolicyDocument = serde_json::from_str(&rds_policy).unwrap();
+
+    let documents = [ec2_policy, rds_policy];
+    let limit = documents.iter().map(PolicyDocument::size).max().unwrap();
+
+    let outcome = pack_policy_documents(&documents, 2, limit, &MergeOptions::default()).unwrap();
+
+    assert!(outcome.documents.iter().all(|d| d.size() <= limit));
+    assert!(outcome.placements.iter().all(|p| !p.is_empty()));
+}
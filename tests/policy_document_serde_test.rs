@@ -0,0 +1,61 @@
+use maip::policy::PolicyDocument;
+
+#[test]
+fn test_deserialize_single_statement_object() {
+    let document: PolicyDocument = serde_json::from_str(
+        r#"{"Version":"2012-10-17","Statement":{"Effect":"Allow","Action":"s3:ListBucket","Resource":"arn:aws:s3:::bucket"}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(document.statement.len(), 1);
+    assert_eq!(document.statement[0].action, vec!["s3:ListBucket"]);
+}
+
+#[test]
+fn test_deserialize_statement_array() {
+    let document: PolicyDocument = serde_json::from_str(
+        r#"{"Version":"2012-10-17","Statement":[{"Effect":"Allow","Action":"s3:ListBucket","Resource":"*"},{"Effect":"Deny","Action":["s3:DeleteBucket","s3:DeleteObject"],"Resource":"*"}]}"#,
+    )
+    .unwrap();
+
+    assert_eq!(document.statement.len(), 2);
+    assert_eq!(
+        document.statement[1].action,
+        vec!["s3:DeleteBucket", "s3:DeleteObject"]
+    );
+}
+
+#[test]
+fn test_deserialize_invalid_statement() {
+    let result = serde_json::from_str::<PolicyDocument>(
+        r#"{"Version":"2012-10-17","Statement":"s3:ListBucket"}"#,
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_deserialize_action_list_with_number() {
+    let result = serde_json::from_str::<PolicyDocument>(
+        r#"{"Version":"2012-10-17","Statement":{"Effect":"Allow","Action":["s3:ListBucket",1],"Resource":"*"}}"#,
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_size_ignores_whitespace() {
+    let document: PolicyDocument = serde_json::from_str(
+        r#"{"Version":"2012-10-17","Statement":{"Effect":"Allow","Action":"s3:ListBucket","Resource":"*","Condition":{"StringEquals":{"aws:PrincipalTag/team":"data platform"}}}}"#,
+    )
+    .unwrap();
+
+    let pretty = serde_json::to_string_pretty(&document).unwrap();
+    let expected_size = pretty.chars().filter(|c| !c.is_whitespace()).count();
+
+    assert_eq!(document.size(), expected_size);
+    assert_eq!(
+        document.size(),
+        serde_json::to_string(&document).unwrap().len() - 1
+    );
+}
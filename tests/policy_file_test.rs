@@ -0,0 +1,77 @@
+use std::{env::temp_dir, fs::write};
+
+use maip::error::MaipError;
+use maip::policy::{policy_from_file, policy_to_file, ConditionValue};
+
+#[test]
+fn test_policy_from_file_round_trip() {
+    let document = policy_from_file("./tests/assets/AmazonRDSFullAccessPolicy.json").unwrap();
+    let path = temp_dir().join("maip_policy_file_round_trip.json");
+    let path = path.to_str().unwrap();
+
+    policy_to_file(path, &document).unwrap();
+
+    assert_eq!(policy_from_file(path).unwrap(), document);
+}
+
+#[test]
+fn test_policy_from_missing_file() {
+    let result = policy_from_file("./tests/assets/DoesNotExist.json");
+
+    assert!(matches!(result, Err(MaipError::Io { .. })));
+}
+
+#[test]
+fn test_policy_from_file_reports_position() {
+    let path = temp_dir().join("maip_policy_file_invalid.json");
+    write(
+        &path,
+        "{\n  \"Version\": \"2012-10-17\",\n  \"Statement\": [\n    {\"Effect\": \"Allow\", \"Action\": 1, \"Resource\": \"*\"}\n  ]\n}",
+    )
+    .unwrap();
+
+    let result = policy_from_file(path.to_str().unwrap());
+
+    match result {
+        Err(MaipError::Parse { path, line, .. }) => {
+            assert!(path.ends_with("maip_policy_file_invalid.json"));
+            assert_eq!(line, 4);
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_policy_from_file_with_numeric_condition_value() {
+    let path = temp_dir().join("maip_policy_file_numeric_condition.json");
+    write(
+        &path,
+        r#"{"Version":"2012-10-17","Statement":[{"Effect":"Allow","Action":"s3:ListBucket","Resource":"*","Condition":{"NumericLessThan":{"s3:max-keys":10}}}]}"#,
+    )
+    .unwrap();
+
+    let document = policy_from_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(
+        document.statement[0].condition[0].conditions[0].values,
+        vec![ConditionValue::Number(10.into())]
+    );
+    assert_eq!(
+        serde_json::to_string(&document.statement[0].condition[0]).unwrap(),
+        r#"{"NumericLessThan":{"s3:max-keys":10}}"#
+    );
+}
+
+#[test]
+fn test_policy_from_file_with_nested_condition_value() {
+    let path = temp_dir().join("maip_policy_file_nested_condition.json");
+    write(
+        &path,
+        r#"{"Version":"2012-10-17","Statement":[{"Effect":"Allow","Action":"s3:ListBucket","Resource":"*","Condition":{"StringEquals":{"s3:prefix":{"a":"b"}}}}]}"#,
+    )
+    .unwrap();
+
+    let result = policy_from_file(path.to_str().unwrap());
+
+    assert!(matches!(result, Err(MaipError::Parse { .. })));
+}
@@ -0,0 +1,91 @@
+use maip::error::MaipError;
+use maip::policy::{merge_policy_documents, PolicyDocument, VERSION_2012_10_17};
+
+#[test]
+fn test_missing_version_is_treated_as_2008() {
+    let document: PolicyDocument = serde_json::from_str(
+        r#"{"Statement":[{"Effect":"Allow","Action":"s3:ListBucket","Resource":"*"}]}"#,
+    )
+    .unwrap();
+
+    assert_eq!(document.version, None);
+    assert_eq!(document.effective_version(), "2008-10-17");
+}
+
+#[test]
+fn test_upgrade_escapes_policy_variables() {
+    let mut document: PolicyDocument = serde_json::from_str(
+        r#"{"Version":"2008-10-17","Statement":[{"Effect":"Allow","Action":"s3:GetObject","Resource":"arn:aws:s3:::bucket/${aws:username}/*"}]}"#,
+    )
+    .unwrap();
+
+    let warnings = document.upgrade().unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(document.version, Some(VERSION_2012_10_17.to_string()));
+    assert_eq!(
+        document.statement[0].resource,
+        vec!["arn:aws:s3:::bucket/${$}{aws:username}/*"]
+    );
+}
+
+#[test]
+fn test_merge_rejects_2008_document_with_policy_variables() {
+    let document: PolicyDocument = serde_json::from_str(
+        r#"{"Version":"2008-10-17","Statement":[{"Effect":"Allow","Action":"s3:GetObject","Resource":"arn:aws:s3:::bucket/${aws:username}/*"}]}"#,
+    )
+    .unwrap();
+
+    assert!(matches!(
+        merge_policy_documents(&[document]),
+        Err(MaipError::UpgradeRequired(_))
+    ));
+}
+
+#[test]
+fn test_merge_accepts_2008_document_without_policy_variables() {
+    let document: PolicyDocument = serde_json::from_str(
+        r#"{"Version":"2008-10-17","Statement":[{"Effect":"Allow","Action":"s3:GetObject","Resource":"*"}]}"#,
+    )
+    .unwrap();
+
+    let merged_document = merge_policy_documents(&[document]).unwrap();
+
+    assert_eq!(merged_document.effective_version(), VERSION_2012_10_17);
+}
+
+#[test]
+fn test_merge_rejects_unknown_version() {
+    let document = PolicyDocument::new("2020-01-01".to_string(), Vec::new());
+
+    assert!(matches!(
+        merge_policy_documents(&[document]),
+        Err(MaipError::UnsupportedVersion(version)) if version == "2020-01-01"
+    ));
+}
+
+#[test]
+fn test_merge_keeps_shared_id() {
+    let first_document =
+        PolicyDocument::new(VERSION_2012_10_17.to_string(), Vec::new()).with_id("BucketPolicy");
+    let second_document =
+        PolicyDocument::new(VERSION_2012_10_17.to_string(), Vec::new()).with_id("BucketPolicy");
+    let third_document = PolicyDocument::new(VERSION_2012_10_17.to_string(), Vec::new());
+
+    let merged_document =
+        merge_policy_documents(&[first_document, second_document, third_document]).unwrap();
+
+    assert_eq!(merged_document.id, Some("BucketPolicy".to_string()));
+}
+
+#[test]
+fn test_merge_drops_conflicting_ids() {
+    let first_document =
+        PolicyDocument::new(VERSION_2012_10_17.to_string(), Vec::new()).with_id("First");
+    let second_document =
+        PolicyDocument::new(VERSION_2012_10_17.to_string(), Vec::new()).with_id("Second");
+
+    let merged_document = merge_policy_documents(&[first_document, second_document]).unwrap();
+
+    assert_eq!(merged_document.id, None);
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use common::{allow, document, statement};
+use maip::policy::{Condition, ConditionStatement};
+
+#[test]
+fn test_reduce_removes_statement_covered_by_wildcard() {
+    let mut document = document(vec![
+        allow(&["s3:*"], &["*"]),
+        allow(&["s3:GetObject"], &["arn:aws:s3:::bucket/*"]),
+    ]);
+
+    document.reduce();
+
+    assert_eq!(document.statement, vec![allow(&["s3:*"], &["*"])]);
+}
+
+#[test]
+fn test_reduce_removes_covered_actions() {
+    let mut document = document(vec![
+        allow(&["s3:Get*"], &["*"]),
+        allow(
+            &["s3:GetObject", "s3:PutObject"],
+            &["arn:aws:s3:::bucket/*"],
+        ),
+    ]);
+
+    document.reduce();
+
+    assert_eq!(
+        document.statement,
+        vec![
+            allow(&["s3:Get*"], &["*"]),
+            allow(&["s3:PutObject"], &["arn:aws:s3:::bucket/*"]),
+        ]
+    );
+}
+
+#[test]
+fn test_reduce_removes_covered_resources() {
+    let mut document = document(vec![
+        allow(&["s3:*"], &["arn:aws:s3:::first-bucket/*"]),
+        allow(
+            &["s3:GetObject"],
+            &[
+                "arn:aws:s3:::first-bucket/*",
+                "arn:aws:s3:::second-bucket/*",
+            ],
+        ),
+    ]);
+
+    document.reduce();
+
+    assert_eq!(
+        document.statement,
+        vec![
+            allow(&["s3:*"], &["arn:aws:s3:::first-bucket/*"]),
+            allow(&["s3:GetObject"], &["arn:aws:s3:::second-bucket/*"]),
+        ]
+    );
+}
+
+#[test]
+fn test_reduce_keeps_one_of_two_identical_statements() {
+    let mut document = document(vec![
+        allow(&["s3:GetObject"], &["*"]),
+        allow(&["s3:GetObject"], &["*"]),
+    ]);
+
+    document.reduce();
+
+    assert_eq!(document.statement, vec![allow(&["s3:GetObject"], &["*"])]);
+}
+
+#[test]
+fn test_reduce_keeps_statement_covered_by_conditional_statement() {
+    let mut conditional = allow(&["s3:*"], &["*"]);
+    conditional.condition = vec![ConditionStatement::new(
+        "StringEquals".parse().unwrap(),
+        vec![Condition::new(
+            "aws:RequestedRegion".to_string(),
+            vec!["eu-west-1"],
+        )],
+    )];
+    let mut document = document(vec![conditional.clone(), allow(&["s3:GetObject"], &["*"])]);
+
+    document.reduce();
+
+    assert_eq!(
+        document.statement,
+        vec![conditional, allow(&["s3:GetObject"], &["*"])]
+    );
+}
+
+#[test]
+fn test_reduce_keeps_deny_statements() {
+    let deny = statement("Deny", &["s3:DeleteBucket"], &["*"]);
+    let mut document = document(vec![allow(&["s3:*"], &["*"]), deny.clone()]);
+
+    document.reduce();
+
+    assert_eq!(document.statement, vec![allow(&["s3:*"], &["*"]), deny]);
+}
+
+#[test]
+fn test_reduce_removes_subsumed_resources_in_statement() {
+    let mut document = document(vec![allow(
+        &["s3:GetObject"],
+        &["arn:aws:s3:::bucket/*", "arn:aws:s3:::bucket/logs/app.log"],
+    )]);
+
+    document.reduce();
+
+    assert_eq!(
+        document.statement,
+        vec![allow(&["s3:GetObject"], &["arn:aws:s3:::bucket/*"])]
+    );
+}
+
+#[test]
+fn test_reduce_removes_statement_with_subsumed_resource() {
+    let mut document = document(vec![
+        allow(&["s3:GetObject"], &["arn:aws:s3:::bucket/*"]),
+        allow(&["s3:GetObject"], &["arn:aws:s3:::bucket/logs/*"]),
+    ]);
+
+    document.reduce();
+
+    assert_eq!(
+        document.statement,
+        vec![allow(&["s3:GetObject"], &["arn:aws:s3:::bucket/*"])]
+    );
+}
+
+#[test]
+fn test_reduce_matches_actions_case_insensitively() {
+    let mut document = document(vec![allow(
+        &[
+            "S3:getobject",
+            "s3:Get*",
+            "ec2:DescribeInstances",
+            "ec2:*Instances",
+        ],
+        &["*"],
+    )]);
+
+    document.reduce();
+
+    assert_eq!(
+        document.statement,
+        vec![allow(&["s3:Get*", "ec2:*Instances"], &["*"])]
+    );
+}
+
+#[test]
+fn test_reduce_removes_statement_covered_by_mid_string_wildcard() {
+    let mut document = document(vec![
+        allow(&["s3:Get?bject"], &["*"]),
+        allow(&["s3:getObject"], &["arn:aws:s3:::bucket/*"]),
+    ]);
+
+    document.reduce();
+
+    assert_eq!(document.statement, vec![allow(&["s3:Get?bject"], &["*"])]);
+}
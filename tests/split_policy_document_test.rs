@@ -0,0 +1,34 @@
+use std::fs::read_to_string;
+
+use maip::policy::{merge_policy_documents, split_policy_document, PolicyDocument};
+
+#[test]
+fn test_split_merged_ec2_and_rds_policy() {
+    let ec2_policy = read_to_string("./tests/assets/AmazonEC2FullAccessPolicy.json").unwrap();
+    let ec2_policy: PolicyDocument = serde_json::from_str(&ec2_policy).unwrap();
+
+    let rds_policy = read_to_string("./tests/assets/AmazonRDSFullAccessPolicy.json").unwrap();
+    let rds_policy: PolicyDocument = serde_json::from_str(&rds_policy).unwrap();
+
+    let merged_policy_document = merge_policy_documents(&[ec2_policy, rds_policy]).unwrap();
+    let limit = merged_policy_document.size() / 2;
+
+    let documents = split_policy_document(&merged_policy_document, limit).unwrap();
+
+    assert!(documents.len() > 1);
+    assert!(documents.iter().all(|document| document.size() <= limit));
+
+    let mut actions = documents
+        .iter()
+        .flat_map(|document| document.statement.iter())
+        .flat_map(|statement| statement.action.clone())
+        .collect::<Vec<String>>();
+    let mut expected_actions = merged_policy_document
+        .statement
+        .iter()
+        .flat_map(|statement| statement.action.clone())
+        .collect::<Vec<String>>();
+    actions.sort();
+    expected_actions.sort();
+    assert_eq!(actions, expected_actions);
+}
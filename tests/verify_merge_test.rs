@@ -0,0 +1,124 @@
+use std::fs::read_to_string;
+
+use maip::catalog::{compress_policy_document, Catalog};
+use maip::policy::{
+    merge_policy_documents_with_options, pack_policy_documents, verify_equivalence, Decision,
+    MergeOptions, MergeStrategy, PolicyDocument, PolicyStatement, VerifyError,
+};
+
+fn read_policy(path: &str) -> PolicyDocument {
+    serde_json::from_str(&read_to_string(path).unwrap()).unwrap()
+}
+
+fn catalog_actions() -> Vec<String> {
+    Catalog::bundled()
+        .unwrap()
+        .actions()
+        .map(|(name, _)| name)
+        .collect()
+}
+
+fn complete_services() -> Vec<String> {
+    Catalog::bundled()
+        .unwrap()
+        .services
+        .into_iter()
+        .filter(|(_, service)| service.complete)
+        .map(|(prefix, _)| prefix)
+        .collect()
+}
+
+#[test]
+fn test_verify_merge_ec2_and_rds_policy() {
+    let policies = [
+        read_policy("./tests/assets/AmazonEC2FullAccessPolicy.json"),
+        read_policy("./tests/assets/AmazonRDSFullAccessPolicy.json"),
+    ];
+
+    for strategy in [MergeStrategy::Greedy, MergeStrategy::Optimal] {
+        let options = MergeOptions {
+            strategy,
+            ..Default::default()
+        };
+        let merged = merge_policy_documents_with_options(&policies, &options)
+            .unwrap()
+            .document;
+
+        assert_eq!(
+            verify_equivalence(
+                &policies,
+                &[merged],
+                &catalog_actions(),
+                &complete_services()
+            ),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn test_verify_packed_policies() {
+    let policies = [
+        read_policy("./tests/assets/AmazonEC2FullAccessPolicy.json"),
+        read_policy("./tests/assets/AmazonRDSFullAccessPolicy.json"),
+    ];
+
+    let outcome = pack_policy_documents(&policies, 10, 800, &MergeOptions::default()).unwrap();
+
+    assert!(outcome.documents.len() > 1);
+    assert_eq!(
+        verify_equivalence(
+            &policies,
+            &outcome.documents,
+            &catalog_actions(),
+            &complete_services()
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_verify_compressed_policy() {
+    let catalog = Catalog::bundled().unwrap();
+    let policy = PolicyDocument::new(
+        "2012-10-17".to_string(),
+        vec![PolicyStatement::new(
+            "Allow".to_string(),
+            catalog.matching_actions("sts:Assume*"),
+            vec!["*".to_string()],
+            Vec::new(),
+        )],
+    );
+
+    let compressed = compress_policy_document(&catalog, &policy);
+
+    assert_eq!(
+        verify_equivalence(
+            &[policy],
+            &[compressed],
+            &catalog_actions(),
+            &complete_services()
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_verify_reports_narrowed_policy() {
+    let policy = read_policy("./tests/assets/AmazonRDSFullAccessPolicy.json");
+    let mut narrowed = policy.clone();
+    narrowed.statement.remove(0);
+
+    let result = verify_equivalence(
+        &[policy],
+        &[narrowed],
+        &catalog_actions(),
+        &complete_services(),
+    );
+
+    let Err(VerifyError::NotEquivalent(counterexample)) = result else {
+        panic!("Expected a counterexample, got {:?}", result);
+    };
+    assert_eq!(counterexample.expected, Decision::Allow);
+    assert_eq!(counterexample.actual, Decision::ImplicitDeny);
+}